@@ -1,5 +1,3 @@
-use log::info;
-use tokio::signal;
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 #[tokio::main]
@@ -13,16 +11,8 @@ async fn main() {
         )
         .init();
 
+    // 消息循环会一直运行, 直到登出或者收到Ctrl-C
     if let Err(err) = openwechat::bootstrap::run().await {
         panic!("Failed to run bot: {}", err);
     }
-
-    match signal::ctrl_c().await {
-        Ok(_) => {
-            info!("Ctrl-C received, shutting down");
-        }
-        Err(e) => {
-            panic!("Failed to listen for Ctrl-C: {}", e);
-        }
-    }
 }
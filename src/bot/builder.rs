@@ -0,0 +1,92 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::Mutex;
+
+use crate::storage::HotReloadStorage;
+
+use super::Bot;
+
+/// [`Bot`]的builder
+///
+/// ```no_run
+/// use openwechat::{bot::Bot, storage::MemoryStorage};
+///
+/// let bot: Bot = Bot::builder()
+///     .hot_reload_storage(MemoryStorage::new())
+///     .build();
+/// ```
+pub struct BotBuilder<S = ()> {
+    state: S,
+    hot_reload_storage: Option<Box<dyn HotReloadStorage>>,
+    forward_delay: Option<Duration>,
+    login_timeout: Option<Duration>,
+    max_qr_refresh: Option<u32>,
+}
+
+impl<S> BotBuilder<S>
+where
+    S: Send + Sync + 'static,
+{
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            hot_reload_storage: None,
+            forward_delay: None,
+            login_timeout: None,
+            max_qr_refresh: None,
+        }
+    }
+
+    /// 设置共享状态, 可以改变状态的类型
+    pub fn state<T>(self, state: T) -> BotBuilder<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        BotBuilder {
+            state,
+            hot_reload_storage: self.hot_reload_storage,
+            forward_delay: self.forward_delay,
+            login_timeout: self.login_timeout,
+            max_qr_refresh: self.max_qr_refresh,
+        }
+    }
+
+    /// 热登录数据的存储, 默认保存到当前目录下的storage.json
+    pub fn hot_reload_storage(mut self, storage: impl HotReloadStorage + 'static) -> Self {
+        self.hot_reload_storage = Some(Box::new(storage));
+        self
+    }
+
+    /// 同[`Bot::set_forward_delay`]
+    pub fn forward_delay(mut self, delay: Duration) -> Self {
+        self.forward_delay = Some(delay);
+        self
+    }
+
+    /// 同[`Bot::set_login_timeout`]
+    pub fn login_timeout(mut self, timeout: Duration) -> Self {
+        self.login_timeout = Some(timeout);
+        self
+    }
+
+    /// 同[`Bot::set_max_qr_refresh`]
+    pub fn max_qr_refresh(mut self, max_qr_refresh: u32) -> Self {
+        self.max_qr_refresh = Some(max_qr_refresh);
+        self
+    }
+
+    pub fn build(self) -> Bot<S> {
+        let mut bot = Bot::with_state(self.state);
+        if let Some(storage) = self.hot_reload_storage {
+            bot.hot_reload_storage = Arc::new(Mutex::new(storage));
+        }
+        if let Some(delay) = self.forward_delay {
+            bot.set_forward_delay(delay);
+        }
+        bot.set_login_timeout(self.login_timeout);
+        if let Some(max_qr_refresh) = self.max_qr_refresh {
+            bot.set_max_qr_refresh(max_qr_refresh);
+        }
+        bot
+    }
+}
@@ -1,44 +1,186 @@
-use std::{sync::Arc, time::Duration};
+use std::{future::Future, sync::Arc, time::Duration};
 
 use log::{debug, info, warn};
 use rand::Rng;
-use tokio::{sync::Mutex, time::sleep};
+use tokio::{
+    sync::{watch, Mutex},
+    time::{sleep, timeout_at, Instant},
+};
 
 use crate::{
-    caller::{Caller, Mode},
+    caller::{Caller, ChatroomUpdate, Mode},
     consts::{Status, REGEX_REDIRECT_URI},
+    contact::{Contact, ContactStore, Contacts, Group, UserName},
     errors::Error,
-    message::{default_message_handler, MessageHandler},
-    resp::{ResponseCheckLogin, ResponseSyncCheck, Selector},
+    handler::{handler_fn, Ctx, Handler},
+    message::{
+        default_message_error_handler, default_message_handler, FriendRequest, MediaBody,
+        MediaFile, MediaId, MediaRange, Message, MessageContext, MessageErrorHandler,
+        MessageHandler, SentMessage,
+    },
+    resp::{LoginInfo, ResponseCheckLogin, ResponseSyncMessage},
     storage::{
-        BaseRequest, HotReloadStorageItem, JSONFileHostReloadStorage, Storage, StorageItemFetcher,
+        BaseRequest, HotReloadStorage, HotReloadStorageItem, JSONFileHostReloadStorage, Storage,
     },
 };
 
-pub struct Bot {
-    /// 定义回调函数类型
-    scan_callback: Option<fn(body: ResponseCheckLogin)>,
+pub use crate::resp::{ResponseSyncCheck, RetCode, Selector};
+pub use builder::BotBuilder;
+pub(crate) use session::Session;
+
+mod builder;
+mod session;
+
+/// 微信机器人, `S`为用户自定义的共享状态, 所有回调都可以通过[`Ctx::state`]访问
+pub struct Bot<S = ()> {
+    /// 共享状态
+    state: Arc<S>,
+    /// 扫码回调
+    scan_callback: Option<Handler<S, ResponseCheckLogin>>,
     /// 登陆回调
-    login_callback: Option<fn(body: ResponseCheckLogin)>,
+    login_callback: Option<Handler<S, ResponseCheckLogin>>,
     /// 登出回调
-    // logout_callback: Option<fn(bot: Bot<T>)>,
+    logout_callback: Option<Handler<S, LogoutReason>>,
     /// 获取UUID的回调
-    uuid_callback: Option<fn(uuid: &str)>,
+    uuid_callback: Option<Handler<S, String>>,
     /// 心跳回调
-    sync_check_callback: Option<fn(body: &ResponseSyncCheck)>,
+    sync_check_callback: Option<Handler<S, ResponseSyncCheck>>,
     /// 获取消息成功的handle
-    message_handler: Option<MessageHandler>,
-    // /// 获取消息发生错误的handle, 返回err == nil 则尝试继续监听
-    // MessageErrorHandler: MessageErrorHandler,
+    message_handler: Option<MessageHandler<S>>,
+    /// 获取消息发生错误的handle, 返回Ok则尝试继续监听
+    message_error_handler: Option<MessageErrorHandler<S>>,
     uuid: String,
     device_id: String,
-    caller: Caller,
+    caller: Arc<Caller>,
     storage: Storage,
-    hot_reload_storage: Arc<Mutex<JSONFileHostReloadStorage>>,
+    contact_store: ContactStore,
+    hot_reload_storage: Arc<Mutex<Box<dyn HotReloadStorage>>>,
+    stop_tx: Arc<watch::Sender<LoopSignal>>,
+    /// 转发给多个会话时的间隔
+    forward_delay: Duration,
+    /// 整个登录流程的最长时间, None表示不限制
+    login_timeout: Option<Duration>,
+    /// 二维码过期后最多重新生成的次数
+    max_qr_refresh: u32,
+}
+
+/// 消息循环出错后重试的间隔
+const SYNC_RETRY_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 默认的转发间隔, 发送过快会返回OperateTooOften
+const DEFAULT_FORWARD_DELAY: Duration = Duration::from_secs(1);
+
+/// 默认的二维码最多刷新次数
+const DEFAULT_MAX_QR_REFRESH: u32 = 3;
+
+/// 退出登录的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogoutReason {
+    /// 调用[`Bot::logout`]主动退出
+    Manual,
+    /// 手机端退出了网页版登录, synccheck retcode 1100
+    PhoneLogout,
+    /// 在其他地方登录了网页版, synccheck retcode 1101
+    LoginElsewhere,
+    /// 登录会话失效, synccheck retcode 1102
+    SessionExpired,
+}
+
+impl LogoutReason {
+    /// 根据synccheck的retcode判断是否被服务端登出
+    pub fn from_ret_code(ret_code: &RetCode) -> Option<Self> {
+        match ret_code {
+            RetCode::LogoutFromPhone => Some(Self::PhoneLogout),
+            RetCode::LogoutElsewhere => Some(Self::LoginElsewhere),
+            RetCode::CookieInvalid => Some(Self::SessionExpired),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LogoutReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Self::Manual => "主动退出登录",
+            Self::PhoneLogout => "手机端退出登录",
+            Self::LoginElsewhere => "在其他地方登录",
+            Self::SessionExpired => "登录会话失效",
+        };
+        write!(f, "{reason}")
+    }
 }
 
-impl Bot {
+/// 通知消息循环的信号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoopSignal {
+    Running,
+    Stop,
+    Logout,
+}
+
+/// 停止消息循环的handle
+#[derive(Clone)]
+pub struct StopHandle(Arc<watch::Sender<LoopSignal>>);
+
+impl StopHandle {
+    pub fn stop(&self) {
+        self.0.send_replace(LoopSignal::Stop);
+    }
+
+    /// 通知消息循环主动退出登录, 由消息循环调用[`Bot::logout`]后返回
+    pub fn logout(&self) {
+        self.0.send_replace(LoopSignal::Logout);
+    }
+}
+
+impl<S> Bot<S>
+where
+    S: Send + Sync + 'static,
+{
+    /// 创建Bot的builder, 可以设置共享状态、热登录存储等
+    pub fn builder() -> BotBuilder<S>
+    where
+        S: Default,
+    {
+        BotBuilder::new(S::default())
+    }
+
+    /// 使用自定义的共享状态创建Bot
+    pub fn with_state(state: S) -> Self {
+        Self {
+            state: Arc::new(state),
+            scan_callback: Default::default(),
+            login_callback: Default::default(),
+            logout_callback: Default::default(),
+            uuid_callback: Default::default(),
+            sync_check_callback: Some(handler_fn(default_sync_check_callback::<S>)),
+            message_handler: Some(handler_fn(default_message_handler::<S>)),
+            message_error_handler: Some(handler_fn(default_message_error_handler::<S>)),
+            uuid: Default::default(),
+            device_id: Default::default(),
+            caller: Default::default(),
+            storage: Default::default(),
+            contact_store: Default::default(),
+            hot_reload_storage: Arc::new(Mutex::new(
+                Box::new(JSONFileHostReloadStorage::default()),
+            )),
+            stop_tx: Arc::new(watch::channel(LoopSignal::Running).0),
+            forward_delay: DEFAULT_FORWARD_DELAY,
+            login_timeout: None,
+            max_qr_refresh: DEFAULT_MAX_QR_REFRESH,
+        }
+    }
+
+    /// 共享状态
+    pub fn state(&self) -> Arc<S> {
+        self.state.clone()
+    }
+
+    /// 热登录, 失败时依次尝试推送登录和扫码登录
+    ///
+    /// 整个流程共用[`Bot::set_login_timeout`]设置的时间
     pub async fn hot_login(&mut self) -> Result<(), Error> {
+        let deadline = self.login_deadline();
         let res = {
             let mut hot_reload_storage = self.hot_reload_storage.lock().await;
             hot_reload_storage.fetch().await
@@ -46,7 +188,7 @@ impl Bot {
         match res {
             Err(e) => {
                 warn!("hot reload storage error: {e}");
-                return self.login().await;
+                return self.login_until(deadline).await;
             }
             Ok(items) => self.hot_login_init(items).await,
         }
@@ -55,9 +197,16 @@ impl Bot {
 
         debug!("device_id: {}", self.device_id);
 
-        if let Err(e) = self.web_init().await {
-            warn!("web init error: {e} try login");
-            return self.login().await;
+        if let Err(e) = with_deadline(deadline, self.web_init()).await {
+            warn!("web init error: {e} try push login");
+            match self.push_login_until(deadline).await {
+                Err(Error::LoginDeadlineExceeded) => return Err(Error::LoginDeadlineExceeded),
+                Err(e) => {
+                    warn!("push login error: {e} try login");
+                    return self.login_until(deadline).await;
+                }
+                Ok(()) => {}
+            }
         }
 
         dbg!(&self.storage);
@@ -65,8 +214,37 @@ impl Bot {
     }
 
     pub async fn login(&mut self) -> Result<(), Error> {
-        let uuid = self.caller.get_login_uuid().await?;
-        self.login_with_uuid(uuid.as_str()).await
+        let deadline = self.login_deadline();
+        self.login_until(deadline).await
+    }
+
+    async fn login_until(&mut self, deadline: Option<Instant>) -> Result<(), Error> {
+        let uuid = with_deadline(deadline, self.caller.get_login_uuid()).await?;
+        self.login_with_uuid_until(&uuid, deadline).await
+    }
+
+    /// 推送登录, 在手机上确认后即可登录, 不需要扫码
+    ///
+    /// 需要之前登录时保存的wxuin和cookie, 通常在热登录之后使用
+    pub async fn push_login(&mut self) -> Result<(), Error> {
+        let deadline = self.login_deadline();
+        self.push_login_until(deadline).await
+    }
+
+    async fn push_login_until(&mut self, deadline: Option<Instant>) -> Result<(), Error> {
+        debug!("bot::push_login");
+        let uin = self
+            .storage
+            .login_info
+            .as_ref()
+            .map(|info| info.wxuin)
+            .or(self.storage.request.as_ref().map(|req| req.uin))
+            .filter(|uin| *uin != 0)
+            .ok_or(Error::PushLogin("没有wxuin".to_owned()))?;
+        let uuid = with_deadline(deadline, self.caller.web_wx_push_login(uin)).await?;
+        info!("已推送登录确认, 请在手机上确认登录");
+        self.uuid = uuid.clone();
+        with_deadline(deadline, self.wait_for_login(&uuid)).await
     }
 
     pub async fn hot_login_init(&mut self, items: HotReloadStorageItem) {
@@ -85,12 +263,53 @@ impl Bot {
     }
 
     /// 使用uuid登录
+    ///
+    /// 二维码过期时重新获取UUID并再次执行UUID回调, 最多刷新[`Bot::set_max_qr_refresh`]次,
+    /// 超过[`Bot::set_login_timeout`]设置的时间后返回[`Error::LoginDeadlineExceeded`]
     pub async fn login_with_uuid(&mut self, uuid: &str) -> Result<(), Error> {
-        self.uuid = uuid.to_string();
-        if let Some(callback) = &self.uuid_callback {
-            callback(uuid);
+        let deadline = self.login_deadline();
+        self.login_with_uuid_until(uuid, deadline).await
+    }
+
+    async fn login_with_uuid_until(
+        &mut self,
+        uuid: &str,
+        deadline: Option<Instant>,
+    ) -> Result<(), Error> {
+        let mut uuid = uuid.to_string();
+        let mut refreshed = 0;
+        loop {
+            self.uuid = uuid.clone();
+            if let Some(callback) = self.uuid_callback.clone() {
+                let ctx = self.ctx(uuid.clone());
+                let callback = async {
+                    callback(ctx).await;
+                    Ok(())
+                };
+                with_deadline(deadline, callback).await?;
+            }
+
+            match with_deadline(deadline, self.wait_for_login(&uuid)).await {
+                Err(Error::LoginTimeout) if refreshed < self.max_qr_refresh => {
+                    refreshed += 1;
+                    info!(
+                        "二维码已过期, 重新生成二维码({refreshed}/{})",
+                        self.max_qr_refresh
+                    );
+                    uuid = with_deadline(deadline, self.caller.get_login_uuid()).await?;
+                }
+                res => return res,
+            }
         }
+    }
 
+    /// 根据[`Bot::set_login_timeout`]计算登录流程的截止时间
+    fn login_deadline(&self) -> Option<Instant> {
+        self.login_timeout.map(|timeout| Instant::now() + timeout)
+    }
+
+    /// 轮询登录状态直到登录成功
+    async fn wait_for_login(&mut self, uuid: &str) -> Result<(), Error> {
         loop {
             let resp = self.caller.check_login(uuid).await?;
             match resp.status {
@@ -110,17 +329,17 @@ impl Bot {
                     let redirect_uri = data.get(1).unwrap().as_str();
                     self.handle_login(redirect_uri).await?;
 
-                    if let Some(login_callback) = self.login_callback.as_ref() {
-                        login_callback(resp);
+                    if let Some(login_callback) = self.login_callback.clone() {
+                        login_callback(self.ctx(resp)).await;
                     }
 
                     return Ok(());
                 }
                 Status::Scanned => {
-                    // 此时 resp.raw 为用户图像数据
+                    // 此时 resp.avatar 为解码后的用户头像
                     info!("请在手机上确认登录");
-                    if let Some(scan_callback) = self.scan_callback.as_ref() {
-                        scan_callback(resp);
+                    if let Some(scan_callback) = self.scan_callback.clone() {
+                        scan_callback(self.ctx(resp)).await;
                     }
                 }
                 Status::Timeout => {
@@ -188,90 +407,548 @@ impl Bot {
             .web_wx_status_notify(base_req, &web_init_resp.user.user_name, login_info)
             .await?;
 
+        // 初始化时返回的是自己和最近联系人
+        self.contact_store.load(
+            std::iter::once(web_init_resp.user.clone())
+                .chain(web_init_resp.contact_list.iter().cloned())
+                .collect(),
+        );
+
         self.storage.web_init_reponse = Some(web_init_resp);
+        self.storage.sync_check_key = None;
 
         Ok(())
     }
 
-    /// 消息循环
+    /// 消息循环, 直到登出或者通过[`StopHandle`]停止才会返回
+    ///
+    /// 没有登录时不会重试, 直接返回[`Error::NotLoggedIn`]
     pub async fn message_loop(&mut self) -> Result<(), Error> {
         debug!("bot::message_loop");
 
-        let base_request = &self
-            .storage
-            .request
-            .as_ref()
-            .ok_or(Error::SyncCheck("没有base request".to_owned()))?;
+        self.stop_tx.send_replace(LoopSignal::Running);
+        let mut stop_rx = self.stop_tx.subscribe();
 
-        let web_init_resp = self
-            .storage
-            .web_init_reponse
-            .as_ref()
-            .ok_or(Error::SyncCheck("没有web_init_reponse".to_owned()))?;
+        loop {
+            // 只在两次同步之间响应停止信号, 已经拉取的消息会全部分发完
+            let signal = *stop_rx.borrow_and_update();
+            match signal {
+                LoopSignal::Running => {}
+                LoopSignal::Stop => {
+                    info!("消息循环已停止");
+                    return Ok(());
+                }
+                LoopSignal::Logout => {
+                    info!("主动退出登录, 消息循环结束");
+                    return self.logout().await;
+                }
+            }
 
-        let login_info = self
+            match self.sync_once(&mut stop_rx).await {
+                Ok(None) => {}
+                Ok(Some(reason)) => {
+                    info!("已退出登录: {reason}, 消息循环结束");
+                    self.handle_logout(reason).await;
+                    return Ok(());
+                }
+                Err(Error::NotLoggedIn) => {
+                    warn!("没有登录, 消息循环结束");
+                    return Err(Error::NotLoggedIn);
+                }
+                Err(e) => {
+                    match self.message_error_handler.clone() {
+                        Some(handler) => handler(self.ctx(e)).await?,
+                        None => return Err(e),
+                    }
+                    tokio::select! {
+                        _ = sleep(SYNC_RETRY_INTERVAL) => {}
+                        _ = stop_rx.wait_for(|signal| *signal != LoopSignal::Running) => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// 执行一次心跳, 有新数据时拉取消息并分发, 被服务端登出时返回登出原因
+    ///
+    /// 收到停止信号时只会中断心跳的长轮询, webwxsync更新SyncKey之后的消息分发不会被中断
+    async fn sync_once(
+        &mut self,
+        stop_rx: &mut watch::Receiver<LoopSignal>,
+    ) -> Result<Option<LogoutReason>, Error> {
+        let device_id = self
             .storage
-            .login_info
+            .request
             .as_ref()
-            .ok_or(Error::SyncCheck("没有login_info".to_owned()))?;
+            .map(|r| r.device_id.clone())
+            .ok_or(Error::NotLoggedIn)?;
+
+        let login_info = self.storage.login_info.clone().ok_or(Error::NotLoggedIn)?;
+
+        let sync_check_key = match self.storage.sync_check_key.clone() {
+            Some(key) => key,
+            None => self
+                .storage
+                .web_init_reponse
+                .as_ref()
+                .map(|r| r.sync_key.clone())
+                .ok_or(Error::NotLoggedIn)?,
+        };
 
-        // loop {
-        let resp = self
-            .caller
-            .sync_check(&base_request.device_id, web_init_resp, login_info)
-            .await?
-            .error()?;
+        let resp = tokio::select! {
+            _ = stop_rx.wait_for(|signal| *signal != LoopSignal::Running) => return Ok(None),
+            resp = self.caller.sync_check(&device_id, &sync_check_key, &login_info) => resp?,
+        };
+
+        if let Some(reason) = LogoutReason::from_ret_code(&resp.ret_code) {
+            return Ok(Some(reason));
+        }
+        let resp = resp.error()?;
 
         // 执行心跳回调
-        if let Some(sync_check_callback) = self.sync_check_callback.as_ref() {
-            sync_check_callback(&resp);
+        if let Some(sync_check_callback) = self.sync_check_callback.clone() {
+            sync_check_callback(self.ctx(resp.clone())).await;
+        }
+
+        if resp.selector == Selector::Normal {
+            return Ok(None);
         }
 
-        if resp.selector != Selector::Normal {
+        loop {
+            let base_request = self.storage.request.clone().ok_or(Error::NotLoggedIn)?;
+            let login_info = self.storage.login_info.clone().ok_or(Error::NotLoggedIn)?;
+            let sync_key = self
+                .storage
+                .web_init_reponse
+                .as_ref()
+                .map(|r| r.sync_key.clone())
+                .ok_or(Error::NotLoggedIn)?;
+
             let resp_sync_msg = self
                 .caller
-                .sync_message(base_request, &web_init_resp.sync_key, login_info)
+                .sync_message(&base_request, &sync_key, &login_info)
                 .await?;
-            // 更新SyncKey并且重新存入storage
-            if let Some(r) = self.storage.web_init_reponse.as_mut() {
-                r.sync_key = resp_sync_msg.sync_key.clone();
+            self.update_sync_state(&resp_sync_msg);
+            self.contact_store.apply_sync(&resp_sync_msg);
+
+            let continue_flag = resp_sync_msg.continue_flag;
+            let session = self.session()?;
+            for mut msg in resp_sync_msg.add_msg_list {
+                let group = self.contact_store.get(&msg.from_user_name);
+                msg.detect_at(session.user(), group.as_ref());
+                debug!(
+                    "收到消息: {} {:?} from {}",
+                    msg.msg_id, msg.msg_type, msg.from_user_name
+                );
+                if let Some(message_handler) = self.message_handler.clone() {
+                    message_handler(self.ctx(MessageContext::new(msg, session.clone()))).await;
+                }
+            }
+
+            // ContinueFlag不为0时表示服务端还有未同步完的数据
+            if continue_flag == 0 {
+                return Ok(None);
             }
-            // if let Some(message_handle) = self.message_handler.as_ref() {
-            //     message_handle();
-            // }
         }
-        sleep(Duration::from_secs(1)).await;
-        // }
+    }
 
+    /// 主动退出登录, 清除热登录保存的信息并执行登出回调
+    ///
+    /// 消息循环运行时通过[`StopHandle::logout`]退出登录
+    pub async fn logout(&mut self) -> Result<(), Error> {
+        debug!("bot::logout");
+        let login_info = self
+            .storage
+            .login_info
+            .as_ref()
+            .ok_or(Error::Logout("没有login_info".to_owned()))?;
+        self.caller.web_wx_logout(login_info).await?;
+        self.handle_logout(LogoutReason::Manual).await;
         Ok(())
     }
 
-    pub fn set_uuid_callback(&mut self, uuid_callback: fn(uuid: &str)) {
-        self.uuid_callback = Some(uuid_callback);
+    /// 清理登录状态并执行登出回调
+    async fn handle_logout(&mut self, reason: LogoutReason) {
+        debug!("bot::handle_logout {reason:?}");
+        self.storage = Storage::default();
+        {
+            let mut hot_reload_storage = self.hot_reload_storage.lock().await;
+            if let Err(e) = hot_reload_storage.clear().await {
+                warn!("clear hot reload storage error: {e}");
+            }
+        }
+
+        if let Some(logout_callback) = self.logout_callback.clone() {
+            logout_callback(self.ctx(reason)).await;
+        }
     }
 
-    // pub fn set_hot_reload_storage(&mut self, hot_reload_storage: T) {
-    //     self.hot_reload_storage = Arc::new(Mutex::new(hot_reload_storage));
-    // }
+    /// 更新SyncKey、SyncCheckKey以及SKey
+    fn update_sync_state(&mut self, resp: &ResponseSyncMessage) {
+        if let Some(r) = self.storage.web_init_reponse.as_mut() {
+            r.sync_key = resp.sync_key.clone();
+        }
+        self.storage.sync_check_key = Some(resp.sync_check_key.clone());
+
+        if !resp.s_key.is_empty() {
+            if let Some(r) = self.storage.request.as_mut() {
+                r.skey = resp.s_key.clone();
+            }
+            if let Some(info) = self.storage.login_info.as_mut() {
+                info.skey = resp.s_key.clone();
+            }
+        }
+    }
+
+    /// 发送文本消息
+    pub async fn send_text(&self, to_user_name: &str, content: &str) -> Result<SentMessage, Error> {
+        debug!("bot::send_text to {to_user_name}");
+        self.session()?.send_text(to_user_name, content).await
+    }
+
+    /// 在群里发送@成员的文本消息, 需要群已经在联系人缓存中
+    pub async fn send_text_at(
+        &self,
+        group_user_name: &str,
+        member_user_names: &[&str],
+        content: &str,
+    ) -> Result<SentMessage, Error> {
+        debug!("bot::send_text_at to {group_user_name}");
+        self.session()?
+            .send_text_at(group_user_name, member_user_names, content)
+            .await
+    }
+
+    /// 发送图片消息
+    pub async fn send_image(
+        &self,
+        to_user_name: &str,
+        file: &MediaFile,
+    ) -> Result<SentMessage, Error> {
+        debug!("bot::send_image to {to_user_name}");
+        self.session()?.send_image(to_user_name, file).await
+    }
+
+    /// 发送视频消息
+    pub async fn send_video(
+        &self,
+        to_user_name: &str,
+        file: &MediaFile,
+    ) -> Result<SentMessage, Error> {
+        debug!("bot::send_video to {to_user_name}");
+        self.session()?.send_video(to_user_name, file).await
+    }
 
-    pub fn set_scan_callback(&mut self, scan_callback: fn(body: ResponseCheckLogin)) {
-        self.scan_callback = Some(scan_callback);
+    /// 发送文件消息
+    pub async fn send_file(
+        &self,
+        to_user_name: &str,
+        file: &MediaFile,
+    ) -> Result<SentMessage, Error> {
+        debug!("bot::send_file to {to_user_name}");
+        self.session()?.send_file(to_user_name, file).await
     }
 
-    pub fn set_login_callback(&mut self, login_callback: fn(body: ResponseCheckLogin)) {
-        self.login_callback = Some(login_callback);
+    /// 发送表情消息
+    pub async fn send_emoticon(
+        &self,
+        to_user_name: &str,
+        file: &MediaFile,
+    ) -> Result<SentMessage, Error> {
+        debug!("bot::send_emoticon to {to_user_name}");
+        self.session()?.send_emoticon(to_user_name, file).await
+    }
+
+    /// 撤回自己发送的消息, 只能撤回2分钟内发送的消息
+    pub async fn revoke(&self, msg: &SentMessage) -> Result<(), Error> {
+        debug!("bot::revoke {}", msg.msg_id);
+        self.session()?.revoke(msg).await
+    }
+
+    /// 转发收到的文本、图片、视频、文件或者表情消息给多个用户或者群
+    ///
+    /// 每个会话之间等待[`Bot::set_forward_delay`]设置的间隔。任意一个会话转发失败时停止,
+    /// 返回[`Error::Forward`], 可以从中获取已经转发成功的消息
+    pub async fn forward(
+        &self,
+        msg: &Message,
+        to_user_names: &[&str],
+    ) -> Result<Vec<SentMessage>, Error> {
+        debug!(
+            "bot::forward {} to {} targets",
+            msg.msg_id,
+            to_user_names.len()
+        );
+        self.session()?
+            .forward(msg, to_user_names, self.forward_delay)
+            .await
+    }
+
+    /// 上传媒体文件, 返回的MediaId可以用于发送图片、视频、文件等消息
+    pub async fn upload_media(
+        &self,
+        to_user_name: &str,
+        file: &MediaFile,
+    ) -> Result<MediaId, Error> {
+        debug!("bot::upload_media {}", file.name());
+        self.session()?.upload_media(to_user_name, file).await
+    }
+
+    /// 下载消息中的图片、语音、视频、表情或者文件
+    pub async fn download_media(&self, msg: &Message) -> Result<MediaBody, Error> {
+        debug!("bot::download_media {}", msg.msg_id);
+        let login_info = self.login_info()?;
+        self.caller
+            .download_media(msg, login_info, false, None)
+            .await
+    }
+
+    /// 下载图片或者视频消息的缩略图
+    pub async fn download_thumbnail(&self, msg: &Message) -> Result<MediaBody, Error> {
+        debug!("bot::download_thumbnail {}", msg.msg_id);
+        if !msg.is_image() && !msg.is_video() {
+            return Err(Error::DownloadMedia(format!(
+                "消息{}不是图片或者视频, 没有缩略图",
+                msg.msg_id
+            )));
+        }
+        let login_info = self.login_info()?;
+        self.caller
+            .download_media(msg, login_info, true, None)
+            .await
     }
 
-    // pub fn set_logout_callback(&mut self, logout_callback: fn(bot: Bot<T>)) {
-    //     self.logout_callback = Some(logout_callback);
-    // }
+    /// 按范围下载视频消息, 用于断点续传或者分段读取
+    pub async fn download_video_range(
+        &self,
+        msg: &Message,
+        range: MediaRange,
+    ) -> Result<MediaBody, Error> {
+        debug!("bot::download_video_range {} {range}", msg.msg_id);
+        if !msg.is_video() {
+            return Err(Error::DownloadMedia(format!("消息{}不是视频", msg.msg_id)));
+        }
+        let login_info = self.login_info()?;
+        self.caller
+            .download_media(msg, login_info, false, Some(range))
+            .await
+    }
+
+    /// 登录信息, 没有登录时返回[`Error::NotLoggedIn`]
+    fn login_info(&self) -> Result<&LoginInfo, Error> {
+        self.storage.login_info.as_ref().ok_or(Error::NotLoggedIn)
+    }
+
+    /// 获取全部联系人并更新到联系人缓存
+    pub async fn fetch_contacts(&self) -> Result<Contacts, Error> {
+        debug!("bot::fetch_contacts");
+        let login_info = self
+            .storage
+            .login_info
+            .as_ref()
+            .ok_or(Error::GetContact("没有login_info".to_owned()))?;
+        let contacts = self.caller.web_wx_get_contact(login_info).await?;
+        self.contact_store.load(contacts.clone());
+        Ok(Contacts::new(contacts))
+    }
+
+    /// 批量获取联系人详情并合并到联系人缓存, 获取群成员详情时需要带上群的EncryChatRoomId
+    pub async fn batch_get_contacts(&self, user_names: &[UserName]) -> Result<Vec<Contact>, Error> {
+        debug!("bot::batch_get_contacts {}", user_names.len());
+        let base_request = self.storage.request.as_ref().ok_or(Error::NoBaseRequest)?;
+        let login_info = self
+            .storage
+            .login_info
+            .as_ref()
+            .ok_or(Error::GetContact("没有login_info".to_owned()))?;
+        let contacts = self
+            .caller
+            .web_wx_batch_get_contact(base_request, login_info, user_names)
+            .await?;
+        for contact in contacts.iter() {
+            self.contact_store.upsert(contact.clone());
+        }
+        Ok(contacts)
+    }
+
+    /// 通过好友请求, 成功后新好友会加入联系人缓存
+    pub async fn accept_friend(&self, request: &FriendRequest) -> Result<(), Error> {
+        debug!("bot::accept_friend {}", request.user_name);
+        self.session()?.accept_friend(request).await
+    }
+
+    /// 向群成员等非好友发送好友请求, `verify_content`为验证消息
+    pub async fn add_friend(&self, user_name: &str, verify_content: &str) -> Result<(), Error> {
+        debug!("bot::add_friend {user_name}");
+        self.session()?.add_friend(user_name, verify_content).await
+    }
+
+    /// 创建群聊, 除自己外至少需要两个好友
+    pub async fn create_group(&self, topic: &str, user_names: &[&str]) -> Result<Group, Error> {
+        debug!("bot::create_group {topic}");
+        self.session()?.create_chatroom(topic, user_names).await
+    }
 
-    pub fn set_sync_check_callback(&mut self, sync_check_callback: fn(body: &ResponseSyncCheck)) {
-        self.sync_check_callback = Some(sync_check_callback);
+    /// 拉好友进群, 返回更新后的群
+    pub async fn add_group_members(
+        &self,
+        group_user_name: &str,
+        user_names: &[&str],
+    ) -> Result<Group, Error> {
+        debug!("bot::add_group_members {group_user_name}");
+        let update = ChatroomUpdate::AddMember(user_names.iter().map(|u| u.to_string()).collect());
+        self.session()?
+            .update_chatroom(group_user_name, &update)
+            .await
     }
 
-    pub fn set_message_handler(&mut self, message_handler: MessageHandler) {
-        self.message_handler = Some(message_handler);
+    /// 移出群成员, 需要是群主
+    pub async fn remove_group_members(
+        &self,
+        group_user_name: &str,
+        user_names: &[&str],
+    ) -> Result<Group, Error> {
+        debug!("bot::remove_group_members {group_user_name}");
+        let update = ChatroomUpdate::DelMember(user_names.iter().map(|u| u.to_string()).collect());
+        self.session()?
+            .update_chatroom(group_user_name, &update)
+            .await
+    }
+
+    /// 发送入群邀请, 群成员超过40人时只能邀请
+    pub async fn invite_group_members(
+        &self,
+        group_user_name: &str,
+        user_names: &[&str],
+    ) -> Result<Group, Error> {
+        debug!("bot::invite_group_members {group_user_name}");
+        let update =
+            ChatroomUpdate::InviteMember(user_names.iter().map(|u| u.to_string()).collect());
+        self.session()?
+            .update_chatroom(group_user_name, &update)
+            .await
+    }
+
+    /// 修改群名称
+    pub async fn rename_group(&self, group_user_name: &str, topic: &str) -> Result<Group, Error> {
+        debug!("bot::rename_group {group_user_name} {topic}");
+        let update = ChatroomUpdate::ModTopic(topic.to_owned());
+        self.session()?
+            .update_chatroom(group_user_name, &update)
+            .await
+    }
+
+    /// 当前缓存的全部联系人
+    pub fn contacts(&self) -> Contacts {
+        self.contact_store.snapshot()
+    }
+
+    /// 联系人缓存, 可以通过[`ContactStore::subscribe`]订阅联系人变更
+    pub fn contact_store(&self) -> ContactStore {
+        self.contact_store.clone()
+    }
+
+    /// 获取发送消息、管理群聊和好友需要的登录会话, 没有登录时返回[`Error::NotLoggedIn`]
+    pub(crate) fn session(&self) -> Result<Session, Error> {
+        let base_request = self.storage.request.clone().ok_or(Error::NotLoggedIn)?;
+        let login_info = self.login_info()?.clone();
+        let web_init_resp = self
+            .storage
+            .web_init_reponse
+            .as_ref()
+            .ok_or(Error::NotLoggedIn)?;
+        Ok(Session::new(
+            self.caller.clone(),
+            self.contact_store.clone(),
+            base_request,
+            login_info,
+            web_init_resp.user.clone(),
+        ))
+    }
+
+    /// 获取用于停止消息循环的handle
+    pub fn stop_handle(&self) -> StopHandle {
+        StopHandle(self.stop_tx.clone())
+    }
+
+    /// 创建回调的上下文
+    fn ctx<T>(&self, data: T) -> Ctx<S, T> {
+        Ctx::new(self.state.clone(), data)
+    }
+
+    pub fn set_uuid_callback<F, Fut>(&mut self, uuid_callback: F)
+    where
+        F: Fn(Ctx<S, String>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.uuid_callback = Some(handler_fn(uuid_callback));
+    }
+
+    /// 设置热登录数据的存储, 默认保存到当前目录下的storage.json
+    pub fn set_hot_reload_storage(&mut self, hot_reload_storage: impl HotReloadStorage + 'static) {
+        self.hot_reload_storage = Arc::new(Mutex::new(Box::new(hot_reload_storage)));
+    }
+
+    pub fn set_scan_callback<F, Fut>(&mut self, scan_callback: F)
+    where
+        F: Fn(Ctx<S, ResponseCheckLogin>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.scan_callback = Some(handler_fn(scan_callback));
+    }
+
+    pub fn set_login_callback<F, Fut>(&mut self, login_callback: F)
+    where
+        F: Fn(Ctx<S, ResponseCheckLogin>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.login_callback = Some(handler_fn(login_callback));
+    }
+
+    pub fn set_logout_callback<F, Fut>(&mut self, logout_callback: F)
+    where
+        F: Fn(Ctx<S, LogoutReason>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.logout_callback = Some(handler_fn(logout_callback));
+    }
+
+    pub fn set_sync_check_callback<F, Fut>(&mut self, sync_check_callback: F)
+    where
+        F: Fn(Ctx<S, ResponseSyncCheck>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.sync_check_callback = Some(handler_fn(sync_check_callback));
+    }
+
+    pub fn set_message_handler<F, Fut>(&mut self, message_handler: F)
+    where
+        F: Fn(Ctx<S, MessageContext>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.message_handler = Some(handler_fn(message_handler));
+    }
+
+    pub fn set_message_error_handler<F, Fut>(&mut self, message_error_handler: F)
+    where
+        F: Fn(Ctx<S, Error>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.message_error_handler = Some(handler_fn(message_error_handler));
+    }
+
+    pub fn set_forward_delay(&mut self, delay: Duration) {
+        self.forward_delay = delay;
+    }
+
+    /// 设置整个登录流程的最长时间, 包括热登录失败后的推送登录、等待扫码、确认、刷新二维码和登录后的初始化, None表示不限制
+    pub fn set_login_timeout(&mut self, timeout: Option<Duration>) {
+        self.login_timeout = timeout;
+    }
+
+    /// 设置二维码过期后最多重新生成的次数, 0表示过期后直接返回[`Error::LoginTimeout`]
+    pub fn set_max_qr_refresh(&mut self, max_qr_refresh: u32) {
+        self.max_qr_refresh = max_qr_refresh;
     }
 
     pub fn set_mode(&mut self, mode: Mode) {
@@ -294,24 +971,28 @@ fn get_random_device_id() -> String {
     device_id // 返回生成的设备 ID
 }
 
-impl Default for Bot {
+impl<S> Default for Bot<S>
+where
+    S: Default + Send + Sync + 'static,
+{
     fn default() -> Self {
-        Self {
-            scan_callback: Default::default(),
-            login_callback: Default::default(),
-            // logout_callback: Default::default(),
-            uuid_callback: Default::default(),
-            sync_check_callback: Some(default_sync_check_callback),
-            message_handler: Some(default_message_handler),
-            uuid: Default::default(),
-            device_id: Default::default(),
-            caller: Default::default(),
-            storage: Default::default(),
-            hot_reload_storage: Default::default(),
-        }
+        Self::with_state(S::default())
+    }
+}
+
+/// 在截止时间之前完成登录流程中的一步, 超时返回[`Error::LoginDeadlineExceeded`]
+async fn with_deadline<T>(
+    deadline: Option<Instant>,
+    fut: impl Future<Output = Result<T, Error>>,
+) -> Result<T, Error> {
+    match deadline {
+        Some(deadline) => timeout_at(deadline, fut)
+            .await
+            .map_err(|_| Error::LoginDeadlineExceeded)?,
+        None => fut.await,
     }
 }
 
-fn default_sync_check_callback(body: &ResponseSyncCheck) {
-    dbg!("default_sync_check_callback", body);
+async fn default_sync_check_callback<S>(ctx: Ctx<S, ResponseSyncCheck>) {
+    debug!("bot::default_sync_check_callback {:?}", ctx.data());
 }
@@ -0,0 +1,496 @@
+use std::{sync::Arc, time::Duration};
+
+use log::{debug, warn};
+use tokio::time::sleep;
+
+use crate::{
+    caller::{Caller, ChatroomUpdate, VerifyOpcode},
+    contact::{Contact, ContactStore, Group, UserName},
+    errors::Error,
+    message::{FriendRequest, MediaBody, MediaFile, MediaId, Message, SendMessage, SentMessage},
+    resp::{LoginInfo, Ret},
+    storage::BaseRequest,
+};
+
+/// 登录会话的快照, 可以在handler和其他任务之间共享, 用于发送消息和查询联系人
+#[derive(Clone)]
+pub(crate) struct Session {
+    caller: Arc<Caller>,
+    contact_store: ContactStore,
+    base_request: BaseRequest,
+    login_info: LoginInfo,
+    /// 当前登录的用户
+    user: Contact,
+}
+
+impl Session {
+    pub(crate) fn new(
+        caller: Arc<Caller>,
+        contact_store: ContactStore,
+        base_request: BaseRequest,
+        login_info: LoginInfo,
+        user: Contact,
+    ) -> Self {
+        Self {
+            caller,
+            contact_store,
+            base_request,
+            login_info,
+            user,
+        }
+    }
+
+    pub(crate) fn user(&self) -> &Contact {
+        &self.user
+    }
+
+    pub(crate) fn contact_store(&self) -> &ContactStore {
+        &self.contact_store
+    }
+
+    async fn send(&self, msg: SendMessage) -> Result<SentMessage, Error> {
+        self.caller
+            .web_wx_send_msg(&self.base_request, &self.login_info, msg)
+            .await
+    }
+
+    pub(crate) async fn send_text(
+        &self,
+        to_user_name: &str,
+        content: &str,
+    ) -> Result<SentMessage, Error> {
+        let msg = SendMessage::text(content, &self.user.user_name, to_user_name);
+        self.send(msg).await
+    }
+
+    /// 在群里发送@成员的文本消息, 优先使用群昵称
+    pub(crate) async fn send_text_at(
+        &self,
+        group_user_name: &str,
+        member_user_names: &[&str],
+        content: &str,
+    ) -> Result<SentMessage, Error> {
+        let group = self
+            .contact_store
+            .get(group_user_name)
+            .filter(Contact::is_group)
+            .ok_or(Error::SendMessage(format!(
+                "联系人缓存中没有群{group_user_name}"
+            )))?;
+
+        let mut names = Vec::with_capacity(member_user_names.len());
+        for user_name in member_user_names {
+            let member = group
+                .member_list
+                .iter()
+                .find(|m| m.user_name == *user_name)
+                .ok_or(Error::SendMessage(format!(
+                    "群{group_user_name}中没有成员{user_name}"
+                )))?;
+            names.push(if member.display_name.is_empty() {
+                member.nick_name.as_str()
+            } else {
+                member.display_name.as_str()
+            });
+        }
+
+        let msg = SendMessage::text_at(&names, content, &self.user.user_name, group_user_name);
+        self.send(msg).await
+    }
+
+    pub(crate) async fn send_image(
+        &self,
+        to_user_name: &str,
+        file: &MediaFile,
+    ) -> Result<SentMessage, Error> {
+        let media_id = self.upload_media(to_user_name, file).await?;
+        let msg = SendMessage::image(&media_id, &self.user.user_name, to_user_name);
+        self.send(msg).await
+    }
+
+    pub(crate) async fn send_video(
+        &self,
+        to_user_name: &str,
+        file: &MediaFile,
+    ) -> Result<SentMessage, Error> {
+        let media_id = self.upload_media(to_user_name, file).await?;
+        let msg = SendMessage::video(&media_id, &self.user.user_name, to_user_name);
+        self.send(msg).await
+    }
+
+    pub(crate) async fn send_file(
+        &self,
+        to_user_name: &str,
+        file: &MediaFile,
+    ) -> Result<SentMessage, Error> {
+        let media_id = self.upload_media(to_user_name, file).await?;
+        let msg = SendMessage::file(
+            &media_id,
+            file.name(),
+            file.size(),
+            &self.user.user_name,
+            to_user_name,
+        );
+        self.send(msg).await
+    }
+
+    pub(crate) async fn send_emoticon(
+        &self,
+        to_user_name: &str,
+        file: &MediaFile,
+    ) -> Result<SentMessage, Error> {
+        let media_id = self.upload_media(to_user_name, file).await?;
+        let msg = SendMessage::emoticon(&media_id, &self.user.user_name, to_user_name);
+        self.send(msg).await
+    }
+
+    pub(crate) async fn upload_media(
+        &self,
+        to_user_name: &str,
+        file: &MediaFile,
+    ) -> Result<MediaId, Error> {
+        self.caller
+            .upload_media(
+                &self.base_request,
+                &self.login_info,
+                file,
+                &self.user.user_name,
+                to_user_name,
+            )
+            .await
+    }
+
+    pub(crate) async fn download_media(&self, msg: &Message) -> Result<MediaBody, Error> {
+        self.caller
+            .download_media(msg, &self.login_info, false, None)
+            .await
+    }
+
+    pub(crate) async fn revoke(&self, msg: &SentMessage) -> Result<(), Error> {
+        self.caller
+            .web_wx_revoke_msg(&self.base_request, &self.login_info, msg)
+            .await
+    }
+
+    /// 通过好友请求, 成功后获取新好友的详情并加入联系人缓存
+    pub(crate) async fn accept_friend(&self, request: &FriendRequest) -> Result<(), Error> {
+        self.caller
+            .web_wx_verify_user(
+                &self.base_request,
+                &self.login_info,
+                VerifyOpcode::AcceptFriend,
+                &request.user_name,
+                &request.ticket,
+                "",
+            )
+            .await?;
+
+        match self
+            .caller
+            .web_wx_batch_get_contact(
+                &self.base_request,
+                &self.login_info,
+                &[UserName::new(request.user_name.as_str())],
+            )
+            .await
+        {
+            Ok(contacts) => contacts
+                .into_iter()
+                .for_each(|contact| self.contact_store.upsert(contact)),
+            Err(e) => warn!("获取新好友{}详情失败: {e}", request.user_name),
+        }
+        Ok(())
+    }
+
+    /// 发送好友请求
+    pub(crate) async fn add_friend(
+        &self,
+        user_name: &str,
+        verify_content: &str,
+    ) -> Result<(), Error> {
+        self.caller
+            .web_wx_verify_user(
+                &self.base_request,
+                &self.login_info,
+                VerifyOpcode::AddFriend,
+                user_name,
+                "",
+                verify_content,
+            )
+            .await
+    }
+
+    /// 创建群聊, 成功后获取群详情并加入联系人缓存
+    pub(crate) async fn create_chatroom(
+        &self,
+        topic: &str,
+        user_names: &[&str],
+    ) -> Result<Group, Error> {
+        let resp = self
+            .caller
+            .web_wx_create_chatroom(&self.base_request, &self.login_info, topic, user_names)
+            .await?;
+        if resp.chat_room_name.is_empty() {
+            return Err(Error::Chatroom(format!(
+                "创建群{topic}失败: 没有返回群UserName"
+            )));
+        }
+        self.refresh_chatroom(&resp.chat_room_name).await
+    }
+
+    /// 修改群聊, 成功后重新获取群详情, 返回最新的群成员列表
+    pub(crate) async fn update_chatroom(
+        &self,
+        chat_room_name: &str,
+        update: &ChatroomUpdate,
+    ) -> Result<Group, Error> {
+        self.caller
+            .web_wx_update_chatroom(&self.base_request, &self.login_info, chat_room_name, update)
+            .await?;
+        self.refresh_chatroom(chat_room_name).await
+    }
+
+    async fn refresh_chatroom(&self, chat_room_name: &str) -> Result<Group, Error> {
+        let contact = self
+            .caller
+            .web_wx_batch_get_contact(
+                &self.base_request,
+                &self.login_info,
+                &[UserName::new(chat_room_name)],
+            )
+            .await?
+            .into_iter()
+            .find(|c| c.user_name == chat_room_name)
+            .ok_or(Error::Chatroom(format!("获取群{chat_room_name}详情失败")))?;
+        self.contact_store.upsert(contact.clone());
+        Ok(Group::new(contact))
+    }
+
+    /// 把收到的消息依次转发给多个会话, 每个会话之间等待`delay`避免操作过于频繁
+    ///
+    /// 媒体消息优先使用原消息的MediaId, 被服务端拒绝时下载后重新上传, 之后的会话使用新的MediaId。
+    /// 任意一个会话失败时停止转发, 返回[`Error::Forward`], 其中包含已经转发成功的消息
+    pub(crate) async fn forward(
+        &self,
+        msg: &Message,
+        to_user_names: &[&str],
+        delay: Duration,
+    ) -> Result<Vec<SentMessage>, Error> {
+        debug!("session::forward {} to {to_user_names:?}", msg.msg_id);
+        if !msg.is_text() && forward_file_name(msg).is_none() {
+            return Err(Error::SendMessage(format!(
+                "不支持转发{:?}类型的消息",
+                msg.msg_type
+            )));
+        }
+
+        let mut media_id = (!msg.media_id.is_empty()).then(|| MediaId::from(msg.media_id.clone()));
+        let mut uploaded = false;
+        let mut sent = Vec::with_capacity(to_user_names.len());
+        for (i, to_user_name) in to_user_names.iter().enumerate() {
+            if i > 0 && !delay.is_zero() {
+                sleep(delay).await;
+            }
+
+            match self
+                .forward_one(msg, to_user_name, &mut media_id, &mut uploaded)
+                .await
+            {
+                Ok(sent_msg) => sent.push(sent_msg),
+                Err(e) => {
+                    return Err(Error::Forward {
+                        sent,
+                        source: Box::new(e),
+                    })
+                }
+            }
+        }
+        Ok(sent)
+    }
+
+    /// 转发给一个会话, 重新上传后更新`media_id`
+    async fn forward_one(
+        &self,
+        msg: &Message,
+        to_user_name: &str,
+        media_id: &mut Option<MediaId>,
+        uploaded: &mut bool,
+    ) -> Result<SentMessage, Error> {
+        if msg.is_text() {
+            return self.send_text(to_user_name, msg.body()).await;
+        }
+
+        let id = match media_id.clone() {
+            Some(id) => id,
+            None => {
+                *uploaded = true;
+                self.reupload(msg, to_user_name).await?
+            }
+        };
+        let send_msg = forward_message(msg, &id, &self.user.user_name, to_user_name);
+        let id = match self.send(send_msg).await {
+            Ok(sent_msg) => {
+                *media_id = Some(id);
+                return Ok(sent_msg);
+            }
+            Err(e) if !*uploaded && is_media_rejected(&e) => {
+                warn!("使用原MediaId转发消息{}失败, 重新上传: {e}", msg.msg_id);
+                *uploaded = true;
+                self.reupload(msg, to_user_name).await?
+            }
+            Err(e) => return Err(e),
+        };
+        let send_msg = forward_message(msg, &id, &self.user.user_name, to_user_name);
+        let sent_msg = self.send(send_msg).await?;
+        *media_id = Some(id);
+        Ok(sent_msg)
+    }
+
+    /// 下载消息中的媒体文件并重新上传
+    async fn reupload(&self, msg: &Message, to_user_name: &str) -> Result<MediaId, Error> {
+        let name = forward_file_name(msg).unwrap_or_default();
+        let data = self.download_media(msg).await?.bytes().await?;
+        let file = MediaFile::new(name, data.to_vec());
+        self.upload_media(to_user_name, &file).await
+    }
+}
+
+/// 服务端拒绝了消息中的MediaId, 操作频繁和登录失效等错误重新上传也不会成功
+fn is_media_rejected(e: &Error) -> bool {
+    match e {
+        Error::SendMessageRet { ret, .. } => !matches!(
+            ret,
+            Ret::OperateTooOften
+                | Ret::FailedLoginWarn
+                | Ret::FailedLoginCheck
+                | Ret::CookieInvalid
+                | Ret::LoginEnvAbnormality
+        ),
+        _ => false,
+    }
+}
+
+/// 重新上传时使用的文件名, 不支持转发的消息返回None
+fn forward_file_name(msg: &Message) -> Option<String> {
+    if msg.is_attachment() {
+        Some(msg.file_name.clone())
+    } else if msg.is_image() {
+        Some(format!("{}.jpg", msg.msg_id))
+    } else if msg.is_video() {
+        Some(format!("{}.mp4", msg.msg_id))
+    } else if msg.is_emoticon() {
+        Some(format!("{}.gif", msg.msg_id))
+    } else {
+        None
+    }
+}
+
+/// 根据原消息类型构造使用MediaId发送的消息
+fn forward_message(
+    msg: &Message,
+    media_id: &MediaId,
+    from_user_name: &str,
+    to_user_name: &str,
+) -> SendMessage {
+    if msg.is_attachment() {
+        let file_size = msg.file_size.parse().unwrap_or_default();
+        SendMessage::file(
+            media_id,
+            &msg.file_name,
+            file_size,
+            from_user_name,
+            to_user_name,
+        )
+    } else if msg.is_image() {
+        SendMessage::image(media_id, from_user_name, to_user_name)
+    } else if msg.is_video() {
+        SendMessage::video(media_id, from_user_name, to_user_name)
+    } else {
+        SendMessage::emoticon(media_id, from_user_name, to_user_name)
+    }
+}
+
+#[cfg(test)]
+impl Session {
+    /// 测试使用的会话, 不能发送请求
+    pub(crate) fn test() -> Self {
+        Self::new(
+            Default::default(),
+            Default::default(),
+            BaseRequest {
+                uin: 0,
+                sid: String::new(),
+                skey: String::new(),
+                device_id: String::new(),
+            },
+            LoginInfo {
+                ret: 0,
+                wxuin: 0,
+                is_gray_scale: 0,
+                message: String::new(),
+                skey: String::new(),
+                wxsid: String::new(),
+                pass_ticket: String::new(),
+            },
+            Contact {
+                user_name: "@self".to_string(),
+                ..Default::default()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::{AppMsgType, MsgType};
+
+    use super::*;
+
+    #[test]
+    fn test_forward_message() {
+        let media_id = MediaId::from("@crypt_abc".to_string());
+        let msg = Message {
+            msg_id: "1".to_string(),
+            msg_type: MsgType::App,
+            app_msg_type: AppMsgType::Attach,
+            file_name: "a.pdf".to_string(),
+            file_size: "1024".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(forward_file_name(&msg).unwrap(), "a.pdf");
+        let send_msg = forward_message(&msg, &media_id, "@self", "@to");
+        assert_eq!(send_msg.msg_type, MsgType::App);
+        assert!(send_msg.content.contains("<totallen>1024</totallen>"));
+
+        let msg = Message {
+            msg_id: "2".to_string(),
+            msg_type: MsgType::Image,
+            ..Default::default()
+        };
+        assert_eq!(forward_file_name(&msg).unwrap(), "2.jpg");
+        let send_msg = forward_message(&msg, &media_id, "@self", "@to");
+        assert_eq!(send_msg.media_id, "@crypt_abc");
+
+        let msg = Message {
+            msg_type: MsgType::Voice,
+            ..Default::default()
+        };
+        assert!(forward_file_name(&msg).is_none());
+    }
+
+    #[test]
+    fn test_is_media_rejected() {
+        let rejected = |ret| {
+            is_media_rejected(&Error::SendMessageRet {
+                ret,
+                errmsg: String::new(),
+            })
+        };
+        assert!(rejected(Ret::Param));
+        assert!(!rejected(Ret::OperateTooOften));
+        assert!(!rejected(Ret::CookieInvalid));
+        assert!(!is_media_rejected(&Error::SendMessage(
+            "timeout".to_owned()
+        )));
+    }
+}
@@ -1,8 +1,12 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{PoisonError, RwLock},
+    time::Duration,
+};
 
 use log::{debug, warn};
 use reqwest::{
-    header::{CONTENT_TYPE, SET_COOKIE},
+    header::{HeaderValue, CONTENT_TYPE, COOKIE, SET_COOKIE},
     redirect::Policy,
     Body, Method, Request, Response,
 };
@@ -13,24 +17,35 @@ use url::Url;
 use crate::{
     caller::http::sync_check,
     consts::{JSON_CONTENT_TYPE, WEB_WX_INIT},
+    contact::UserName,
     errors::Error,
+    message::{MediaFile, MediaRange, Message, SendMessage, SentMessage},
     resp::{
-        LoginInfo, ResponseCheckLogin, ResponseSyncCheck, ResponseSyncMessage, ResponseWebInit,
-        SyncKey,
+        LoginInfo, ResponseBatchGetContact, ResponseCheckLogin, ResponseCheckUpload,
+        ResponseCreateChatroom, ResponseGetContact, ResponsePushLogin, ResponseRevokeMsg,
+        ResponseSendMessage, ResponseSyncCheck, ResponseSyncMessage, ResponseUpdateChatroom,
+        ResponseUploadMedia, ResponseVerifyUser, ResponseWebInit, SyncKey,
     },
     storage::{BaseRequest, WechatDomain},
 };
 
 use super::http::{
-    check_login, get_login_info, get_login_uuid, sync_message, web_wx_status_notify, Mode,
+    check_login, get_login_info, get_login_uuid, sync_message, web_wx_batch_get_contact,
+    web_wx_check_upload, web_wx_create_chatroom, web_wx_get_contact, web_wx_get_media,
+    web_wx_logout, web_wx_push_login, web_wx_revoke_msg, web_wx_send_msg, web_wx_status_notify,
+    web_wx_update_chatroom, web_wx_upload_media, web_wx_verify_user, ChatroomUpdate, Mode,
+    VerifyOpcode,
 };
 
 pub struct Client {
     client: reqwest::Client,
+    /// 下载媒体文件使用的client, 大文件读取时间较长, 只限制连接和单次读取的超时
+    media_client: reqwest::Client,
     hooks: Option<Vec<Box<dyn HttpHook>>>,
-    domain: Option<WechatDomain>,
+    /// 登录后才能确定域名, 消息循环期间Client会被共享, 所以使用锁
+    domain: RwLock<Option<WechatDomain>>,
     cookies: Mutex<HashMap<String, CookieStore>>,
-    pub mode: Mode,
+    mode: RwLock<Mode>,
 }
 
 impl Default for Client {
@@ -50,23 +65,39 @@ impl Client {
                 .redirect(Policy::none()) // 默认会自动重定向
                 .build()
                 .unwrap(),
+            media_client: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(30))
+                .read_timeout(Duration::from_secs(30))
+                .redirect(Policy::none())
+                .build()
+                .unwrap(),
             hooks: None,
-            domain: None,
-            mode,
+            domain: RwLock::new(None),
+            mode: RwLock::new(mode),
             cookies: Mutex::new(HashMap::new()),
         }
     }
 
-    pub fn set_mode(&mut self, mode: Mode) {
-        self.mode = mode;
+    pub fn set_mode(&self, mode: Mode) {
+        *self.mode.write().unwrap_or_else(PoisonError::into_inner) = mode;
     }
 
-    pub fn set_domain(&mut self, domain: Option<WechatDomain>) {
-        self.domain = domain;
+    pub fn mode(&self) -> Mode {
+        self.mode
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn set_domain(&self, domain: Option<WechatDomain>) {
+        *self.domain.write().unwrap_or_else(PoisonError::into_inner) = domain;
     }
 
     pub fn get_domain(&self) -> Option<WechatDomain> {
-        self.domain.clone()
+        self.domain
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
     }
     pub fn add_http_hook(&mut self, hooks: Vec<Box<dyn HttpHook>>) {
         if let Some(h) = &mut self.hooks {
@@ -76,7 +107,7 @@ impl Client {
         }
     }
 
-    async fn do_http(&self, mut req: Request) -> Result<Response, Error> {
+    async fn do_http(&self, client: &reqwest::Client, mut req: Request) -> Result<Response, Error> {
         if let Some(hooks) = &self.hooks {
             for hook in hooks {
                 hook.before_request(&mut req);
@@ -87,7 +118,7 @@ impl Client {
         let mut err = None;
         for i in 0..MAX_RETRY {
             let req = req.try_clone().ok_or(Error::RequestClone)?;
-            match self.client.execute(req).await {
+            match client.execute(req).await {
                 Ok(r) => {
                     resp = Some(r);
                     break;
@@ -121,10 +152,8 @@ impl Client {
                 let hv = hv.to_owned();
                 let xs = String::from_utf8(hv.as_bytes().to_vec())
                     .map(|s| {
-                        let xx = cookie::Cookie::split_parse(s)
-                            .filter_map(|r| r.ok()) // TODO:
-                            .collect::<Vec<cookie::Cookie<'static>>>();
-                        xx
+                        // 每个Set-Cookie头是一个cookie, 分号后面是Domain、Path等属性
+                        cookie::Cookie::parse(s).into_iter().collect::<Vec<_>>()
                     })
                     .unwrap(); // TODO:
                 xs
@@ -147,12 +176,19 @@ impl Client {
         cookies.insert(path, store);
     }
 
-    pub async fn add_cookies(&mut self, (url, cookie): (String, CookieStore)) {
+    pub async fn add_cookies(&self, (url, cookie): (String, CookieStore)) {
         self.cookies.lock().await.insert(url, cookie);
     }
 
     pub async fn execute(&self, req: Request) -> Result<Response, Error> {
-        let resp = self.do_http(req).await?;
+        let resp = self.do_http(&self.client, req).await?;
+        self.parse_cookies(&resp).await;
+        Ok(resp)
+    }
+
+    /// 下载媒体文件, 响应体可能需要很长时间才能读完, 不限制请求的总时间
+    pub async fn execute_media(&self, req: Request) -> Result<Response, Error> {
+        let resp = self.do_http(&self.media_client, req).await?;
         self.parse_cookies(&resp).await;
         Ok(resp)
     }
@@ -162,16 +198,60 @@ impl Client {
         cookies.clone()
     }
 
+    /// 从已保存的cookie中查找指定名称的值
+    pub async fn get_cookie(&self, name: &str) -> Option<String> {
+        let cookies = self.cookies.lock().await;
+        let value = cookies
+            .values()
+            .flat_map(|store| store.iter_any())
+            .find(|c| c.name() == name)
+            .map(|c| c.value().to_string());
+        value
+    }
+
+    /// 将域名和路径匹配请求url的cookie附加到请求头中, 文件服务器需要cookie鉴权
+    pub async fn attach_cookies(&self, req: &mut Request) {
+        let cookies = self.cookies.lock().await;
+        let mut pairs = BTreeMap::new();
+        for (name, value) in cookies
+            .values()
+            .flat_map(|store| store.get_request_values(req.url()))
+        {
+            // 过滤掉旧版本热登录数据中被当作cookie保存下来的属性
+            if COOKIE_ATTRIBUTES.contains(&name.to_ascii_lowercase().as_str()) {
+                continue;
+            }
+            pairs.insert(name.to_string(), value.to_string());
+        }
+        if pairs.is_empty() {
+            return;
+        }
+
+        let cookie = pairs
+            .into_iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if let Ok(value) = HeaderValue::from_str(&cookie) {
+            req.headers_mut().insert(COOKIE, value);
+        }
+    }
+
     /// 获取登录uuid
     pub async fn get_login_uuid(&self) -> Result<String, Error> {
         get_login_uuid(self).await
     }
 
+    pub async fn web_wx_push_login(&self, uin: i64) -> Result<ResponsePushLogin, Error> {
+        debug!("client::web_wx_push_login");
+        web_wx_push_login(self, uin).await
+    }
+
     pub async fn check_login(&self, uuid: &str) -> Result<ResponseCheckLogin, Error> {
         check_login(self, uuid).await
     }
 
-    pub async fn get_login_info(&mut self, url: &str) -> Result<LoginInfo, Error> {
+    pub async fn get_login_info(&self, url: &str) -> Result<LoginInfo, Error> {
         debug!("client::get_login_info {}", url);
         get_login_info(self, url).await
     }
@@ -181,8 +261,7 @@ impl Client {
         debug!("client::web_init");
         let init_url_str = format!(
             "{}{}",
-            self.domain
-                .as_ref()
+            self.get_domain()
                 .ok_or(Error::WebInit("no domain".to_string()))?
                 .base_host(),
             WEB_WX_INIT
@@ -207,6 +286,11 @@ impl Client {
         Ok(res)
     }
 
+    pub async fn web_wx_logout(&self, login_info: &LoginInfo) -> Result<(), Error> {
+        debug!("client::web_wx_logout");
+        web_wx_logout(self, login_info).await
+    }
+
     pub async fn web_wx_status_notify(
         &self,
         base_req: &BaseRequest,
@@ -220,11 +304,11 @@ impl Client {
     pub async fn sync_check(
         &self,
         device_id: &str,
-        web_init_resp: &ResponseWebInit,
+        sync_key: &SyncKey,
         login_info: &LoginInfo,
     ) -> Result<ResponseSyncCheck, Error> {
         debug!("client::sync_check");
-        sync_check(self, device_id, web_init_resp, login_info).await
+        sync_check(self, device_id, sync_key, login_info).await
     }
 
     pub async fn sync_message(
@@ -235,11 +319,142 @@ impl Client {
     ) -> Result<ResponseSyncMessage, Error> {
         sync_message(self, base_req, sync_key, login_info).await
     }
+
+    pub async fn web_wx_send_msg(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        msg: &SendMessage,
+    ) -> Result<ResponseSendMessage, Error> {
+        debug!("client::web_wx_send_msg");
+        web_wx_send_msg(self, base_req, login_info, msg).await
+    }
+
+    pub async fn web_wx_revoke_msg(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        msg: &SentMessage,
+    ) -> Result<ResponseRevokeMsg, Error> {
+        debug!("client::web_wx_revoke_msg");
+        web_wx_revoke_msg(self, base_req, login_info, msg).await
+    }
+
+    pub async fn web_wx_create_chatroom(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        topic: &str,
+        user_names: &[&str],
+    ) -> Result<ResponseCreateChatroom, Error> {
+        debug!("client::web_wx_create_chatroom");
+        web_wx_create_chatroom(self, base_req, login_info, topic, user_names).await
+    }
+
+    pub async fn web_wx_update_chatroom(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        chat_room_name: &str,
+        update: &ChatroomUpdate,
+    ) -> Result<ResponseUpdateChatroom, Error> {
+        debug!("client::web_wx_update_chatroom");
+        web_wx_update_chatroom(self, base_req, login_info, chat_room_name, update).await
+    }
+
+    pub async fn web_wx_verify_user(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        opcode: VerifyOpcode,
+        user_name: &str,
+        ticket: &str,
+        verify_content: &str,
+    ) -> Result<ResponseVerifyUser, Error> {
+        debug!("client::web_wx_verify_user");
+        web_wx_verify_user(
+            self,
+            base_req,
+            login_info,
+            opcode,
+            user_name,
+            ticket,
+            verify_content,
+        )
+        .await
+    }
+
+    pub async fn web_wx_check_upload(
+        &self,
+        base_req: &BaseRequest,
+        file: &MediaFile,
+        from_user_name: &str,
+        to_user_name: &str,
+    ) -> Result<ResponseCheckUpload, Error> {
+        debug!("client::web_wx_check_upload");
+        web_wx_check_upload(self, base_req, file, from_user_name, to_user_name).await
+    }
+
+    pub async fn web_wx_upload_media(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        file: &MediaFile,
+        from_user_name: &str,
+        to_user_name: &str,
+        check: &ResponseCheckUpload,
+    ) -> Result<ResponseUploadMedia, Error> {
+        debug!("client::web_wx_upload_media");
+        web_wx_upload_media(
+            self,
+            base_req,
+            login_info,
+            file,
+            from_user_name,
+            to_user_name,
+            check,
+        )
+        .await
+    }
+
+    pub async fn web_wx_get_media(
+        &self,
+        msg: &Message,
+        login_info: &LoginInfo,
+        thumbnail: bool,
+        range: Option<MediaRange>,
+    ) -> Result<Response, Error> {
+        debug!("client::web_wx_get_media");
+        web_wx_get_media(self, msg, login_info, thumbnail, range).await
+    }
+
+    pub async fn web_wx_get_contact(
+        &self,
+        login_info: &LoginInfo,
+        seq: i64,
+    ) -> Result<ResponseGetContact, Error> {
+        debug!("client::web_wx_get_contact");
+        web_wx_get_contact(self, login_info, seq).await
+    }
+
+    pub async fn web_wx_batch_get_contact(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        user_names: &[UserName],
+    ) -> Result<ResponseBatchGetContact, Error> {
+        debug!("client::web_wx_batch_get_contact");
+        web_wx_batch_get_contact(self, base_req, login_info, user_names).await
+    }
 }
 
 const MAX_RETRY: u8 = 3;
 
-pub trait HttpHook {
+const COOKIE_ATTRIBUTES: [&str; 7] = [
+    "domain", "path", "expires", "max-age", "secure", "httponly", "samesite",
+];
+
+pub trait HttpHook: Send + Sync {
     fn before_request(&self, req: &mut Request);
     fn after_request(&self, resp: &Response);
 }
@@ -254,3 +469,47 @@ impl HttpHook for UserAgentHook {
 
     fn after_request(&self, _resp: &Response) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_attach_cookies() {
+        let client = Client::default();
+        let login_url = Url::parse("https://login.wx.qq.com/cgi-bin/mmwebwx-bin/login").unwrap();
+        let mut login_store = CookieStore::new(None);
+        login_store
+            .parse("wxuin=1; Domain=login.wx.qq.com; Path=/", &login_url)
+            .unwrap();
+        client
+            .add_cookies((login_url.to_string(), login_store))
+            .await;
+
+        let init_url = Url::parse("https://wx2.qq.com/cgi-bin/mmwebwx-bin/webwxinit").unwrap();
+        let mut init_store = CookieStore::new(None);
+        init_store
+            .parse("wxuin=2; Domain=wx2.qq.com; Path=/", &init_url)
+            .unwrap();
+        init_store
+            .parse(
+                "webwx_data_ticket=abc; Domain=wx2.qq.com; Path=/",
+                &init_url,
+            )
+            .unwrap();
+        client.add_cookies((init_url.to_string(), init_store)).await;
+
+        let url = Url::parse("https://file.wx2.qq.com/cgi-bin/mmwebwx-bin/webwxgetmedia").unwrap();
+        let mut req = Request::new(Method::GET, url);
+        client.attach_cookies(&mut req).await;
+        assert_eq!(
+            req.headers().get(COOKIE).unwrap(),
+            "webwx_data_ticket=abc; wxuin=2"
+        );
+
+        let url = Url::parse("https://qq.com/").unwrap();
+        let mut req = Request::new(Method::GET, url);
+        client.attach_cookies(&mut req).await;
+        assert!(req.headers().get(COOKIE).is_none());
+    }
+}
@@ -1,6 +1,9 @@
-use chrono::Utc;
+use chrono::{FixedOffset, Utc};
 use log::debug;
-use reqwest::{header::CONTENT_TYPE, Body, Method, StatusCode};
+use reqwest::{
+    header::{HeaderValue, CONTENT_TYPE, RANGE},
+    Body, Method, Response, StatusCode,
+};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
@@ -10,12 +13,21 @@ use crate::{
         Status, APP_ID, JSON_CONTENT_TYPE, JS_LOGIN, LOGIN, REGEX_STATUS_CODE, REGEX_SYNC_CHECK,
         REGEX_UUID, STATUS_CODE_SCANNED, STATUS_CODE_SUCCESS, STATUS_CODE_TIMEOUT,
         STATUS_CODE_WAIT, SYNC_CHECK, UOS_PATCH_CLIENT_VERSION, UOS_PATCH_EXTSPAM,
-        WEB_WX_NEW_LOGIN_PAGE, WEB_WX_STATUS_NOTIFY, WEB_WX_SYNC,
+        WEB_WX_BATCH_GET_CONTACT, WEB_WX_CHECK_UPLOAD, WEB_WX_CREATE_CHATROOM, WEB_WX_GET_CONTACT,
+        WEB_WX_GET_MEDIA, WEB_WX_GET_MSG_IMG, WEB_WX_GET_VIDEO, WEB_WX_GET_VOICE, WEB_WX_LOGOUT,
+        WEB_WX_NEW_LOGIN_PAGE, WEB_WX_PUSH_LOGIN_URL, WEB_WX_REVOKE_MSG, WEB_WX_SENDMSG,
+        WEB_WX_SEND_APP_MSG, WEB_WX_SEND_EMOTICON, WEB_WX_SEND_MSG_IMG, WEB_WX_SEND_VIDEO_MSG,
+        WEB_WX_STATUS_NOTIFY, WEB_WX_SYNC, WEB_WX_UPDATE_CHATROOM, WEB_WX_UPLOA_DMEDIA,
+        WEB_WX_VERIFY_USER,
     },
+    contact::UserName,
     errors::Error,
+    message::{MediaFile, MediaRange, Message, MsgType, SendMessage, SentMessage},
     resp::{
-        BaseResponse, LoginInfo, ResponseCheckLogin, ResponseSyncCheck, ResponseSyncMessage,
-        ResponseWebInit, SyncKey,
+        BaseResponse, LoginInfo, ResponseBatchGetContact, ResponseCheckLogin, ResponseCheckUpload,
+        ResponseCreateChatroom, ResponseGetContact, ResponsePushLogin, ResponseRevokeMsg,
+        ResponseSendMessage, ResponseSyncCheck, ResponseSyncMessage, ResponseUpdateChatroom,
+        ResponseUploadMedia, ResponseVerifyUser, SyncKey,
     },
     storage::{BaseRequest, WechatDomain},
 };
@@ -44,10 +56,10 @@ pub async fn get_login_uuid(client: &Client) -> Result<String, Error> {
     let mut redirect_url = Url::parse(WEB_WX_NEW_LOGIN_PAGE).map_err(|e| {
         Error::GetLoginUuid(format!("解析url: {WEB_WX_NEW_LOGIN_PAGE} 失败:\n {e}"))
     })?;
-    if client.mode == Mode::Desktop {
+    if client.mode() == Mode::Desktop {
         redirect_url
             .query_pairs_mut()
-            .append_pair("mod", client.mode.as_str());
+            .append_pair("mod", client.mode().as_str());
     }
 
     let mut login_url = Url::parse(JS_LOGIN)
@@ -59,7 +71,7 @@ pub async fn get_login_uuid(client: &Client) -> Result<String, Error> {
         .append_pair("fun", "new")
         .append_pair("lang", "zh_CN")
         .append_pair("_", &format!("{}", Utc::now().timestamp_millis()));
-    if client.mode == Mode::Desktop {
+    if client.mode() == Mode::Desktop {
         login_url.query_pairs_mut().append_pair("mod", MODE_DESKTOP);
     }
 
@@ -86,6 +98,46 @@ pub async fn get_login_uuid(client: &Client) -> Result<String, Error> {
     Ok(uuid)
 }
 
+/// 向手机推送登录确认, 需要之前登录时的wxuin和cookie
+pub async fn web_wx_push_login(client: &Client, uin: i64) -> Result<ResponsePushLogin, Error> {
+    debug!("web_wx_push_login {uin}");
+    let req = push_login_request(client, uin).await?;
+    let path = req.url().to_string();
+    let resp: ResponsePushLogin = client
+        .execute(req)
+        .await
+        .map_err(|e| Error::PushLogin(format!("请求url: {path} 失败:\n {e}")))?
+        .json()
+        .await
+        .map_err(|e| Error::PushLogin(format!("解析webwxpushloginurl数据失败: {e}")))?;
+
+    if !resp.is_ok() {
+        return Err(Error::PushLogin(format!(
+            "推送登录失败: {} {}",
+            resp.ret, resp.msg
+        )));
+    }
+
+    Ok(resp)
+}
+
+/// 推送登录的请求, 携带之前登录时保存的cookie
+async fn push_login_request(client: &Client, uin: i64) -> Result<reqwest::Request, Error> {
+    let domain = client
+        .get_domain()
+        .ok_or(Error::PushLogin("没有登录过的域名".to_owned()))?;
+    let path = format!("{}{}", domain.base_host(), WEB_WX_PUSH_LOGIN_URL);
+    let mut push_url =
+        Url::parse(&path).map_err(|e| Error::PushLogin(format!("解析url: {path} 失败:\n {e}")))?;
+    push_url
+        .query_pairs_mut()
+        .append_pair("uin", &uin.to_string());
+
+    let mut req = reqwest::Request::new(Method::GET, push_url);
+    client.attach_cookies(&mut req).await;
+    Ok(req)
+}
+
 /// 检查登录状态
 pub async fn check_login(client: &Client, uuid: &str) -> Result<ResponseCheckLogin, Error> {
     let mut login_url = Url::parse(LOGIN)
@@ -125,7 +177,7 @@ pub async fn check_login(client: &Client, uuid: &str) -> Result<ResponseCheckLog
         STATUS_CODE_WAIT => Status::Wait,
         _ => Status::Unknown(status_code.to_string()),
     };
-    Ok(ResponseCheckLogin { status, raw: resp })
+    Ok(ResponseCheckLogin::new(status, resp))
 }
 
 /// 通知微信状态
@@ -188,7 +240,7 @@ struct ResponseWebWxStatusNotify {
 }
 
 /// 获取登录信息
-pub async fn get_login_info(client: &mut Client, url: &str) -> Result<LoginInfo, Error> {
+pub async fn get_login_info(client: &Client, url: &str) -> Result<LoginInfo, Error> {
     let u = Url::parse(url)
         .map_err(|e| Error::GetLoginInfo(format!("解析redirect uri: {url} 失败:\n {e}")))?;
 
@@ -199,7 +251,7 @@ pub async fn get_login_info(client: &mut Client, url: &str) -> Result<LoginInfo,
 
     let mut req = reqwest::Request::new(Method::GET, u);
 
-    match client.mode {
+    match client.mode() {
         Mode::Desktop => {
             let headers = req.headers_mut();
             headers.append("client-version", UOS_PATCH_CLIENT_VERSION);
@@ -234,7 +286,7 @@ pub async fn get_login_info(client: &mut Client, url: &str) -> Result<LoginInfo,
 pub async fn sync_check(
     client: &Client,
     device_id: &str,
-    web_init_resp: &ResponseWebInit,
+    sync_key: &SyncKey,
     login_info: &LoginInfo,
 ) -> Result<ResponseSyncCheck, Error> {
     debug!("sync_check");
@@ -244,8 +296,7 @@ pub async fn sync_check(
         .map_err(|e| Error::SyncCheck(format!("解析sync check url: {path} 失败:\n {e}")))?;
 
     let timestamp = Utc::now().timestamp();
-    let sync_key = web_init_resp
-        .sync_key
+    let sync_key = sync_key
         .list
         .iter()
         .map(|kv| format!("{kv}"))
@@ -334,7 +385,853 @@ pub async fn sync_message(
 
     let resp: ResponseSyncMessage = client.execute(req).await?.json().await?;
 
-    dbg!(&resp);
+    debug!("sync_message ret: {:?}", resp.base_response.ret);
+    if !resp.base_response.is_ok() {
+        return Err(Error::Sync(format!(
+            "webwxsync失败: {:?} {}",
+            resp.base_response.ret, resp.base_response.errmsg
+        )));
+    }
+
+    Ok(resp)
+}
+
+/// 发送消息, 根据消息类型选择文本、图片、视频、APP或者表情接口
+pub async fn web_wx_send_msg(
+    client: &Client,
+    base_req: &BaseRequest,
+    login_info: &LoginInfo,
+    msg: &SendMessage,
+) -> Result<ResponseSendMessage, Error> {
+    debug!("web_wx_send_msg {:?}", msg.msg_type);
+    let (api, fun) = match msg.msg_type {
+        MsgType::Image => (WEB_WX_SEND_MSG_IMG, Some("async")),
+        MsgType::Video => (WEB_WX_SEND_VIDEO_MSG, Some("async")),
+        MsgType::App => (WEB_WX_SEND_APP_MSG, Some("async")),
+        MsgType::Emoticon => (WEB_WX_SEND_EMOTICON, Some("sys")),
+        _ => (WEB_WX_SENDMSG, None),
+    };
+    let path = format!("{}{}", client.get_domain().unwrap().base_host(), api);
+
+    let mut send_url = Url::parse(&path)
+        .map_err(|e| Error::SendMessage(format!("解析url: {path} 失败:\n {e}")))?;
+    if let Some(fun) = fun {
+        send_url
+            .query_pairs_mut()
+            .append_pair("fun", fun)
+            .append_pair("f", "json");
+    }
+    send_url
+        .query_pairs_mut()
+        .append_pair("lang", "zh_CN")
+        .append_pair("pass_ticket", &login_info.pass_ticket);
+
+    let content = serde_json::json!({
+        "BaseRequest": base_req,
+        "Msg": msg,
+        "Scene": 0,
+    });
+
+    let mut req = reqwest::Request::new(Method::POST, send_url);
+    *req.body_mut() = Some(Body::from(serde_json::to_vec(&content).unwrap()));
+    req.headers_mut().append(CONTENT_TYPE, JSON_CONTENT_TYPE);
+
+    let resp: ResponseSendMessage = client
+        .execute(req)
+        .await
+        .map_err(|e| Error::SendMessage(format!("请求url: {path} 失败:\n {e}")))?
+        .json()
+        .await
+        .map_err(|e| Error::SendMessage(format!("解析{api}响应数据失败: {e}")))?;
+
+    if !resp.base_response.is_ok() {
+        return Err(Error::SendMessageRet {
+            ret: resp.base_response.ret,
+            errmsg: resp.base_response.errmsg,
+        });
+    }
+
+    Ok(resp)
+}
+
+/// 撤回自己发送的消息
+pub async fn web_wx_revoke_msg(
+    client: &Client,
+    base_req: &BaseRequest,
+    login_info: &LoginInfo,
+    msg: &SentMessage,
+) -> Result<ResponseRevokeMsg, Error> {
+    debug!("web_wx_revoke_msg {}", msg.msg_id);
+    let path = format!(
+        "{}{}",
+        client.get_domain().unwrap().base_host(),
+        WEB_WX_REVOKE_MSG
+    );
+    let mut revoke_url = Url::parse(&path)
+        .map_err(|e| Error::RevokeMessage(format!("解析url: {path} 失败:\n {e}")))?;
+    revoke_url
+        .query_pairs_mut()
+        .append_pair("lang", "zh_CN")
+        .append_pair("pass_ticket", &login_info.pass_ticket);
+
+    let content = serde_json::json!({
+        "BaseRequest": base_req,
+        "ClientMsgId": msg.local_id,
+        "SvrMsgId": msg.msg_id,
+        "ToUserName": msg.to_user_name,
+    });
+
+    let mut req = reqwest::Request::new(Method::POST, revoke_url);
+    *req.body_mut() = Some(Body::from(serde_json::to_vec(&content).unwrap()));
+    req.headers_mut().append(CONTENT_TYPE, JSON_CONTENT_TYPE);
+
+    let resp: ResponseRevokeMsg = client
+        .execute(req)
+        .await
+        .map_err(|e| Error::RevokeMessage(format!("请求url: {path} 失败:\n {e}")))?
+        .json()
+        .await
+        .map_err(|e| Error::RevokeMessage(format!("解析webwxrevokemsg数据失败: {e}")))?;
+
+    if !resp.base_response.is_ok() {
+        return Err(Error::RevokeMessage(format!(
+            "撤回消息{}失败: {:?} {}",
+            msg.msg_id, resp.base_response.ret, resp.base_response.errmsg
+        )));
+    }
+
+    Ok(resp)
+}
+
+/// 群聊修改操作, 对应webwxupdatechatroom的fun参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ChatroomUpdate {
+    /// 直接拉人进群
+    AddMember(Vec<String>),
+    /// 移出群成员, 需要是群主
+    DelMember(Vec<String>),
+    /// 发送入群邀请, 群人数较多时只能邀请
+    InviteMember(Vec<String>),
+    /// 修改群名称
+    ModTopic(String),
+}
+
+impl ChatroomUpdate {
+    pub(crate) fn fun(&self) -> &'static str {
+        match self {
+            Self::AddMember(_) => "addmember",
+            Self::DelMember(_) => "delmember",
+            Self::InviteMember(_) => "invitemember",
+            Self::ModTopic(_) => "modtopic",
+        }
+    }
+
+    fn content(&self, base_req: &BaseRequest, chat_room_name: &str) -> serde_json::Value {
+        let mut content = serde_json::json!({
+            "BaseRequest": base_req,
+            "ChatRoomName": chat_room_name,
+        });
+        let (key, value) = match self {
+            Self::AddMember(user_names) => ("AddMemberList", user_names.join(",")),
+            Self::DelMember(user_names) => ("DelMemberList", user_names.join(",")),
+            Self::InviteMember(user_names) => ("InviteMemberList", user_names.join(",")),
+            Self::ModTopic(topic) => ("NewTopic", topic.clone()),
+        };
+        content[key] = serde_json::Value::String(value);
+        content
+    }
+}
+
+/// 创建群聊, 至少需要两个好友
+pub async fn web_wx_create_chatroom(
+    client: &Client,
+    base_req: &BaseRequest,
+    login_info: &LoginInfo,
+    topic: &str,
+    user_names: &[&str],
+) -> Result<ResponseCreateChatroom, Error> {
+    debug!("web_wx_create_chatroom {topic}");
+    let path = format!(
+        "{}{}",
+        client.get_domain().unwrap().base_host(),
+        WEB_WX_CREATE_CHATROOM
+    );
+    let mut create_url =
+        Url::parse(&path).map_err(|e| Error::Chatroom(format!("解析url: {path} 失败:\n {e}")))?;
+    create_url
+        .query_pairs_mut()
+        .append_pair("r", &Utc::now().timestamp_millis().to_string())
+        .append_pair("lang", "zh_CN")
+        .append_pair("pass_ticket", &login_info.pass_ticket);
+
+    let member_list = user_names
+        .iter()
+        .map(|user_name| serde_json::json!({ "UserName": user_name }))
+        .collect::<Vec<_>>();
+    let content = serde_json::json!({
+        "BaseRequest": base_req,
+        "MemberCount": user_names.len(),
+        "MemberList": member_list,
+        "Topic": topic,
+    });
+
+    let mut req = reqwest::Request::new(Method::POST, create_url);
+    *req.body_mut() = Some(Body::from(serde_json::to_vec(&content).unwrap()));
+    req.headers_mut().append(CONTENT_TYPE, JSON_CONTENT_TYPE);
+
+    let resp: ResponseCreateChatroom = client
+        .execute(req)
+        .await
+        .map_err(|e| Error::Chatroom(format!("请求url: {path} 失败:\n {e}")))?
+        .json()
+        .await
+        .map_err(|e| Error::Chatroom(format!("解析webwxcreatechatroom数据失败: {e}")))?;
+
+    check_chatroom_response(&resp.base_response)?;
+    Ok(resp)
+}
+
+/// 群聊接口的返回码不为0时返回[`Error::ChatroomRet`]
+fn check_chatroom_response(base_response: &BaseResponse) -> Result<(), Error> {
+    if !base_response.is_ok() {
+        return Err(Error::ChatroomRet {
+            ret: base_response.ret,
+            errmsg: base_response.errmsg.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// 修改群聊: 增删成员、邀请成员或修改群名称
+pub async fn web_wx_update_chatroom(
+    client: &Client,
+    base_req: &BaseRequest,
+    login_info: &LoginInfo,
+    chat_room_name: &str,
+    update: &ChatroomUpdate,
+) -> Result<ResponseUpdateChatroom, Error> {
+    debug!("web_wx_update_chatroom {chat_room_name} {}", update.fun());
+    let path = format!(
+        "{}{}",
+        client.get_domain().unwrap().base_host(),
+        WEB_WX_UPDATE_CHATROOM
+    );
+    let mut update_url =
+        Url::parse(&path).map_err(|e| Error::Chatroom(format!("解析url: {path} 失败:\n {e}")))?;
+    update_url
+        .query_pairs_mut()
+        .append_pair("fun", update.fun())
+        .append_pair("lang", "zh_CN")
+        .append_pair("pass_ticket", &login_info.pass_ticket);
+
+    let content = update.content(base_req, chat_room_name);
+
+    let mut req = reqwest::Request::new(Method::POST, update_url);
+    *req.body_mut() = Some(Body::from(serde_json::to_vec(&content).unwrap()));
+    req.headers_mut().append(CONTENT_TYPE, JSON_CONTENT_TYPE);
+
+    let resp: ResponseUpdateChatroom = client
+        .execute(req)
+        .await
+        .map_err(|e| Error::Chatroom(format!("请求url: {path} 失败:\n {e}")))?
+        .json()
+        .await
+        .map_err(|e| Error::Chatroom(format!("解析webwxupdatechatroom数据失败: {e}")))?;
+
+    check_chatroom_response(&resp.base_response)?;
+    Ok(resp)
+}
+
+/// webwxverifyuser的操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VerifyOpcode {
+    /// 发送好友请求
+    AddFriend,
+    /// 通过好友请求
+    AcceptFriend,
+}
+
+impl VerifyOpcode {
+    fn code(self) -> i32 {
+        match self {
+            Self::AddFriend => 2,
+            Self::AcceptFriend => 3,
+        }
+    }
+}
+
+/// 添加好友或者通过好友请求, 添加好友时ticket为空
+pub async fn web_wx_verify_user(
+    client: &Client,
+    base_req: &BaseRequest,
+    login_info: &LoginInfo,
+    opcode: VerifyOpcode,
+    user_name: &str,
+    ticket: &str,
+    verify_content: &str,
+) -> Result<ResponseVerifyUser, Error> {
+    debug!("web_wx_verify_user {user_name} {opcode:?}");
+    let path = format!(
+        "{}{}",
+        client.get_domain().unwrap().base_host(),
+        WEB_WX_VERIFY_USER
+    );
+    let mut verify_url =
+        Url::parse(&path).map_err(|e| Error::VerifyUser(format!("解析url: {path} 失败:\n {e}")))?;
+    verify_url
+        .query_pairs_mut()
+        .append_pair("r", &Utc::now().timestamp_millis().to_string())
+        .append_pair("lang", "zh_CN")
+        .append_pair("pass_ticket", &login_info.pass_ticket);
+
+    let content = verify_user_content(base_req, opcode, user_name, ticket, verify_content);
+
+    let mut req = reqwest::Request::new(Method::POST, verify_url);
+    *req.body_mut() = Some(Body::from(serde_json::to_vec(&content).unwrap()));
+    req.headers_mut().append(CONTENT_TYPE, JSON_CONTENT_TYPE);
+
+    let resp: ResponseVerifyUser = client
+        .execute(req)
+        .await
+        .map_err(|e| Error::VerifyUser(format!("请求url: {path} 失败:\n {e}")))?
+        .json()
+        .await
+        .map_err(|e| Error::VerifyUser(format!("解析webwxverifyuser数据失败: {e}")))?;
+
+    if !resp.base_response.is_ok() {
+        return Err(Error::VerifyUserRet {
+            ret: resp.base_response.ret,
+            errmsg: resp.base_response.errmsg,
+        });
+    }
+
+    Ok(resp)
+}
+
+fn verify_user_content(
+    base_req: &BaseRequest,
+    opcode: VerifyOpcode,
+    user_name: &str,
+    ticket: &str,
+    verify_content: &str,
+) -> serde_json::Value {
+    serde_json::json!({
+        "BaseRequest": base_req,
+        "Opcode": opcode.code(),
+        "SceneList": [33],
+        "SceneListCount": 1,
+        "VerifyContent": verify_content,
+        "VerifyUserList": [{
+            "Value": user_name,
+            "VerifyUserTicket": ticket,
+        }],
+        "VerifyUserListSize": 1,
+        "skey": base_req.skey,
+    })
+}
+
+/// 检查文件是否已经上传过
+pub async fn web_wx_check_upload(
+    client: &Client,
+    base_req: &BaseRequest,
+    file: &MediaFile,
+    from_user_name: &str,
+    to_user_name: &str,
+) -> Result<ResponseCheckUpload, Error> {
+    debug!("web_wx_check_upload");
+    let path = format!(
+        "{}{}",
+        client.get_domain().unwrap().base_host(),
+        WEB_WX_CHECK_UPLOAD
+    );
+    let check_url = Url::parse(&path)
+        .map_err(|e| Error::UploadMedia(format!("解析url: {path} 失败:\n {e}")))?;
+
+    let content = serde_json::json!({
+        "BaseRequest": base_req,
+        "FileMd5": file.md5(),
+        "FileName": file.name(),
+        "FileSize": file.size(),
+        "FileType": 7,
+        "FromUserName": from_user_name,
+        "ToUserName": to_user_name,
+    });
+
+    let mut req = reqwest::Request::new(Method::POST, check_url);
+    *req.body_mut() = Some(Body::from(serde_json::to_vec(&content).unwrap()));
+    req.headers_mut().append(CONTENT_TYPE, JSON_CONTENT_TYPE);
+
+    let resp: ResponseCheckUpload = client
+        .execute(req)
+        .await
+        .map_err(|e| Error::UploadMedia(format!("请求url: {path} 失败:\n {e}")))?
+        .json()
+        .await
+        .map_err(|e| Error::UploadMedia(format!("解析webwxcheckupload数据失败: {e}")))?;
+
+    if !resp.base_response.is_ok() {
+        return Err(Error::UploadMedia(format!(
+            "webwxcheckupload失败: {:?} {}",
+            resp.base_response.ret, resp.base_response.errmsg
+        )));
+    }
+
+    Ok(resp)
+}
+
+/// 分片上传的大小
+const UPLOAD_CHUNK_SIZE: usize = 512 * 1024;
+
+/// 分片上传媒体文件, 返回最后一个分片的响应
+pub async fn web_wx_upload_media(
+    client: &Client,
+    base_req: &BaseRequest,
+    login_info: &LoginInfo,
+    file: &MediaFile,
+    from_user_name: &str,
+    to_user_name: &str,
+    check: &ResponseCheckUpload,
+) -> Result<ResponseUploadMedia, Error> {
+    debug!("web_wx_upload_media {} {}", file.name(), file.size());
+    let path = format!(
+        "{}{}",
+        client.get_domain().unwrap().file_host(),
+        WEB_WX_UPLOA_DMEDIA
+    );
+    let mut upload_url = Url::parse(&path)
+        .map_err(|e| Error::UploadMedia(format!("解析url: {path} 失败:\n {e}")))?;
+    upload_url.query_pairs_mut().append_pair("f", "json");
+
+    let data_ticket = client
+        .get_cookie("webwx_data_ticket")
+        .await
+        .ok_or(Error::UploadMedia(
+            "cookie中没有webwx_data_ticket".to_owned(),
+        ))?;
+
+    let upload_media_request = serde_json::json!({
+        "UploadType": 2,
+        "BaseRequest": base_req,
+        "ClientMediaId": Utc::now().timestamp_millis(),
+        "TotalLen": file.size(),
+        "StartPos": 0,
+        "DataLen": file.size(),
+        "MediaType": 4,
+        "FromUserName": from_user_name,
+        "ToUserName": to_user_name,
+        "FileMd5": file.md5(),
+        "AESKey": check.aes_key,
+        "Signature": check.signature,
+    });
+
+    let content_type = file.content_type();
+    let last_modified = Utc::now()
+        .with_timezone(&FixedOffset::east_opt(8 * 3600).unwrap())
+        .format("%a %b %d %Y %H:%M:%S GMT+0800 (中国标准时间)")
+        .to_string();
+    let chunks = file.data().chunks(UPLOAD_CHUNK_SIZE).collect::<Vec<_>>();
+
+    let mut resp = None;
+    for (index, chunk) in chunks.iter().enumerate() {
+        let mut fields = vec![
+            ("id", "WU_FILE_0".to_string()),
+            ("name", file.name().to_string()),
+            ("type", content_type.clone()),
+            ("lastModifiedDate", last_modified.clone()),
+            ("size", file.size().to_string()),
+            ("mediatype", file.media_type().to_string()),
+            ("uploadmediarequest", upload_media_request.to_string()),
+            ("webwx_data_ticket", data_ticket.clone()),
+            ("pass_ticket", login_info.pass_ticket.clone()),
+        ];
+        if chunks.len() > 1 {
+            fields.push(("chunks", chunks.len().to_string()));
+            fields.push(("chunk", index.to_string()));
+        }
+
+        let boundary = format!("----WebKitFormBoundary{}", Utc::now().timestamp_millis());
+        let body = multipart_body(&boundary, &fields, file.name(), &content_type, chunk);
+
+        let mut req = reqwest::Request::new(Method::POST, upload_url.clone());
+        *req.body_mut() = Some(Body::from(body));
+        req.headers_mut().append(
+            CONTENT_TYPE,
+            HeaderValue::from_str(&format!("multipart/form-data; boundary={boundary}")).unwrap(),
+        );
+        client.attach_cookies(&mut req).await;
+
+        let r: ResponseUploadMedia = client
+            .execute(req)
+            .await
+            .map_err(|e| Error::UploadMedia(format!("请求url: {path} 失败:\n {e}")))?
+            .json()
+            .await
+            .map_err(|e| Error::UploadMedia(format!("解析webwxuploadmedia数据失败: {e}")))?;
+
+        if !r.base_response.is_ok() {
+            return Err(Error::UploadMedia(format!(
+                "webwxuploadmedia第{index}个分片上传失败: {:?} {}",
+                r.base_response.ret, r.base_response.errmsg
+            )));
+        }
+        resp = Some(r);
+    }
+
+    resp.ok_or(Error::UploadMedia("文件内容为空".to_owned()))
+}
+
+/// 组装multipart/form-data请求体, 使用字节数组以便请求失败时可以重试
+fn multipart_body(
+    boundary: &str,
+    fields: &[(&str, String)],
+    file_name: &str,
+    content_type: &str,
+    data: &[u8],
+) -> Vec<u8> {
+    let mut body = Vec::with_capacity(data.len() + 2048);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!("--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n")
+                .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"filename\"; filename=\"{file_name}\"\r\nContent-Type: {content_type}\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(data);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    body
+}
+
+/// 下载消息中的媒体文件, 根据消息类型选择图片、语音、视频或者文件接口
+pub async fn web_wx_get_media(
+    client: &Client,
+    msg: &Message,
+    login_info: &LoginInfo,
+    thumbnail: bool,
+    range: Option<MediaRange>,
+) -> Result<Response, Error> {
+    debug!("web_wx_get_media {} {:?}", msg.msg_id, msg.msg_type);
+    let domain = client.get_domain().unwrap();
+
+    let path = match msg.msg_type {
+        MsgType::Image | MsgType::Emoticon => {
+            format!("{}{}", domain.base_host(), WEB_WX_GET_MSG_IMG)
+        }
+        MsgType::Video | MsgType::MicroVideo if thumbnail => {
+            format!("{}{}", domain.base_host(), WEB_WX_GET_MSG_IMG)
+        }
+        MsgType::Voice => format!("{}{}", domain.base_host(), WEB_WX_GET_VOICE),
+        MsgType::Video | MsgType::MicroVideo => {
+            format!("{}{}", domain.base_host(), WEB_WX_GET_VIDEO)
+        }
+        MsgType::App if !msg.media_id.is_empty() => {
+            format!("{}{}", domain.file_host(), WEB_WX_GET_MEDIA)
+        }
+        _ => {
+            return Err(Error::DownloadMedia(format!(
+                "消息{}的类型{:?}不支持下载",
+                msg.msg_id, msg.msg_type
+            )))
+        }
+    };
+
+    let mut media_url = Url::parse(&path)
+        .map_err(|e| Error::DownloadMedia(format!("解析url: {path} 失败:\n {e}")))?;
+
+    match msg.msg_type {
+        MsgType::Image | MsgType::Emoticon => {
+            media_url
+                .query_pairs_mut()
+                .append_pair("MsgID", &msg.msg_id)
+                .append_pair("skey", &login_info.skey);
+            if thumbnail {
+                media_url.query_pairs_mut().append_pair("type", "slave");
+            }
+        }
+        MsgType::Video | MsgType::MicroVideo if thumbnail => {
+            media_url
+                .query_pairs_mut()
+                .append_pair("MsgID", &msg.msg_id)
+                .append_pair("skey", &login_info.skey)
+                .append_pair("type", "slave");
+        }
+        MsgType::App => {
+            let data_ticket =
+                client
+                    .get_cookie("webwx_data_ticket")
+                    .await
+                    .ok_or(Error::DownloadMedia(
+                        "cookie中没有webwx_data_ticket".to_owned(),
+                    ))?;
+            media_url
+                .query_pairs_mut()
+                .append_pair("sender", &msg.from_user_name)
+                .append_pair("mediaid", &msg.media_id)
+                .append_pair("encryfilename", &msg.encry_file_name)
+                .append_pair("fromuser", &login_info.wxuin.to_string())
+                .append_pair("pass_ticket", &login_info.pass_ticket)
+                .append_pair("webwx_data_ticket", &data_ticket);
+        }
+        _ => {
+            media_url
+                .query_pairs_mut()
+                .append_pair("msgid", &msg.msg_id)
+                .append_pair("skey", &login_info.skey);
+        }
+    }
+
+    let mut req = reqwest::Request::new(Method::GET, media_url);
+    // 视频接口必须带上Range头
+    if matches!(msg.msg_type, MsgType::Video | MsgType::MicroVideo) && !thumbnail {
+        let range = range.unwrap_or_default().to_string();
+        req.headers_mut()
+            .append(RANGE, HeaderValue::from_str(&range).unwrap());
+    }
+    client.attach_cookies(&mut req).await;
+
+    let resp = client
+        .execute_media(req)
+        .await
+        .map_err(|e| Error::DownloadMedia(format!("请求url: {path} 失败:\n {e}")))?;
+
+    if !resp.status().is_success() {
+        return Err(Error::DownloadMedia(format!(
+            "下载消息{}的媒体文件失败: {}",
+            msg.msg_id,
+            resp.status()
+        )));
+    }
 
     Ok(resp)
 }
+
+/// 分页获取联系人列表
+pub async fn web_wx_get_contact(
+    client: &Client,
+    login_info: &LoginInfo,
+    seq: i64,
+) -> Result<ResponseGetContact, Error> {
+    debug!("web_wx_get_contact seq: {seq}");
+    let path = format!(
+        "{}{}",
+        client.get_domain().unwrap().base_host(),
+        WEB_WX_GET_CONTACT
+    );
+    let mut contact_url =
+        Url::parse(&path).map_err(|e| Error::GetContact(format!("解析url: {path} 失败:\n {e}")))?;
+    contact_url
+        .query_pairs_mut()
+        .append_pair("r", &Utc::now().timestamp_millis().to_string())
+        .append_pair("seq", &seq.to_string())
+        .append_pair("skey", &login_info.skey)
+        .append_pair("lang", "zh_CN")
+        .append_pair("pass_ticket", &login_info.pass_ticket);
+
+    let mut req = reqwest::Request::new(Method::GET, contact_url);
+    client.attach_cookies(&mut req).await;
+
+    let resp: ResponseGetContact = client
+        .execute(req)
+        .await
+        .map_err(|e| Error::GetContact(format!("请求url: {path} 失败:\n {e}")))?
+        .json()
+        .await
+        .map_err(|e| Error::GetContact(format!("解析webwxgetcontact数据失败: {e}")))?;
+
+    if !resp.base_response.is_ok() {
+        return Err(Error::GetContact(format!(
+            "webwxgetcontact失败: {:?} {}",
+            resp.base_response.ret, resp.base_response.errmsg
+        )));
+    }
+
+    Ok(resp)
+}
+
+/// 批量获取联系人详情
+pub async fn web_wx_batch_get_contact(
+    client: &Client,
+    base_req: &BaseRequest,
+    login_info: &LoginInfo,
+    user_names: &[UserName],
+) -> Result<ResponseBatchGetContact, Error> {
+    debug!("web_wx_batch_get_contact count: {}", user_names.len());
+    let path = format!(
+        "{}{}",
+        client.get_domain().unwrap().base_host(),
+        WEB_WX_BATCH_GET_CONTACT
+    );
+    let mut contact_url =
+        Url::parse(&path).map_err(|e| Error::GetContact(format!("解析url: {path} 失败:\n {e}")))?;
+    contact_url
+        .query_pairs_mut()
+        .append_pair("type", "ex")
+        .append_pair("r", &Utc::now().timestamp_millis().to_string())
+        .append_pair("lang", "zh_CN")
+        .append_pair("pass_ticket", &login_info.pass_ticket);
+
+    let content = serde_json::json!({
+        "BaseRequest": base_req,
+        "Count": user_names.len(),
+        "List": user_names,
+    });
+
+    let mut req = reqwest::Request::new(Method::POST, contact_url);
+    *req.body_mut() = Some(Body::from(serde_json::to_vec(&content).unwrap()));
+    req.headers_mut().append(CONTENT_TYPE, JSON_CONTENT_TYPE);
+    client.attach_cookies(&mut req).await;
+
+    let resp: ResponseBatchGetContact = client
+        .execute(req)
+        .await
+        .map_err(|e| Error::GetContact(format!("请求url: {path} 失败:\n {e}")))?
+        .json()
+        .await
+        .map_err(|e| Error::GetContact(format!("解析webwxbatchgetcontact数据失败: {e}")))?;
+
+    if !resp.base_response.is_ok() {
+        return Err(Error::GetContact(format!(
+            "webwxbatchgetcontact失败: {:?} {}",
+            resp.base_response.ret, resp.base_response.errmsg
+        )));
+    }
+
+    Ok(resp)
+}
+
+/// 退出登录
+pub async fn web_wx_logout(client: &Client, login_info: &LoginInfo) -> Result<(), Error> {
+    debug!("web_wx_logout");
+    let path = format!(
+        "{}{}",
+        client.get_domain().unwrap().base_host(),
+        WEB_WX_LOGOUT
+    );
+    let mut logout_url =
+        Url::parse(&path).map_err(|e| Error::Logout(format!("解析url: {path} 失败:\n {e}")))?;
+    logout_url
+        .query_pairs_mut()
+        .append_pair("redirect", "1")
+        .append_pair("type", "1")
+        .append_pair("skey", &login_info.skey);
+
+    let content = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("sid", &login_info.wxsid)
+        .append_pair("uin", &login_info.wxuin.to_string())
+        .finish();
+
+    let mut req = reqwest::Request::new(Method::POST, logout_url);
+    *req.body_mut() = Some(Body::from(content));
+    req.headers_mut().append(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/x-www-form-urlencoded"),
+    );
+    client.attach_cookies(&mut req).await;
+
+    // 成功时返回重定向, 不需要解析响应内容
+    let resp = client
+        .execute(req)
+        .await
+        .map_err(|e| Error::Logout(format!("请求url: {path} 失败:\n {e}")))?;
+    if resp.status().is_client_error() || resp.status().is_server_error() {
+        return Err(Error::Logout(format!("webwxlogout失败: {}", resp.status())));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chatroom_update_content() {
+        let base_req = BaseRequest {
+            uin: 1,
+            sid: "sid".to_string(),
+            skey: "skey".to_string(),
+            device_id: "e123".to_string(),
+        };
+
+        let update = ChatroomUpdate::AddMember(vec!["@a".to_string(), "@b".to_string()]);
+        assert_eq!(update.fun(), "addmember");
+        let content = update.content(&base_req, "@@group");
+        assert_eq!(content["ChatRoomName"], "@@group");
+        assert_eq!(content["AddMemberList"], "@a,@b");
+
+        let update = ChatroomUpdate::ModTopic("新群名".to_string());
+        assert_eq!(update.fun(), "modtopic");
+        let content = update.content(&base_req, "@@group");
+        assert_eq!(content["NewTopic"], "新群名");
+        assert!(content.get("AddMemberList").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_push_login_request() {
+        let client = Client::default();
+        client.set_domain(Some(WechatDomain::new("wx2.qq.com".to_string())));
+        let url = Url::parse("https://wx2.qq.com/cgi-bin/mmwebwx-bin/webwxinit").unwrap();
+        let mut store = reqwest_cookie_store::CookieStore::new(None);
+        store
+            .parse("wxuin=2; Domain=wx2.qq.com; Path=/", &url)
+            .unwrap();
+        client.add_cookies((url.to_string(), store)).await;
+
+        let req = push_login_request(&client, 2).await.unwrap();
+        assert_eq!(
+            req.url().as_str(),
+            "https://wx2.qq.com/cgi-bin/mmwebwx-bin/webwxpushloginurl?uin=2"
+        );
+        assert_eq!(
+            req.headers().get(reqwest::header::COOKIE).unwrap(),
+            "wxuin=2"
+        );
+    }
+
+    #[test]
+    fn test_check_chatroom_response() {
+        let resp: ResponseUpdateChatroom =
+            serde_json::from_str(r#"{"BaseResponse":{"Ret":-23,"ErrMsg":""},"MemberCount":0}"#)
+                .unwrap();
+        match check_chatroom_response(&resp.base_response) {
+            Err(Error::ChatroomRet { ret, .. }) => assert_eq!(ret, crate::resp::Ret::Unknown(-23)),
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_verify_user_content() {
+        let base_req = BaseRequest {
+            uin: 1,
+            sid: "sid".to_string(),
+            skey: "skey".to_string(),
+            device_id: "e123".to_string(),
+        };
+
+        let content = verify_user_content(
+            &base_req,
+            VerifyOpcode::AcceptFriend,
+            "@stranger",
+            "v4_abc",
+            "",
+        );
+        assert_eq!(content["Opcode"], 3);
+        assert_eq!(content["skey"], "skey");
+        assert_eq!(content["VerifyUserList"][0]["Value"], "@stranger");
+        assert_eq!(content["VerifyUserList"][0]["VerifyUserTicket"], "v4_abc");
+
+        let content = verify_user_content(
+            &base_req,
+            VerifyOpcode::AddFriend,
+            "@friend",
+            "",
+            "我是张三",
+        );
+        assert_eq!(content["Opcode"], 2);
+        assert_eq!(content["VerifyContent"], "我是张三");
+    }
+}
@@ -2,15 +2,21 @@ use std::collections::HashMap;
 
 use client::Client;
 pub use http::Mode;
+pub(crate) use http::{ChatroomUpdate, VerifyOpcode};
 use log::debug;
 use reqwest_cookie_store::CookieStore;
 
+use crate::contact::{Contact, UserName};
+use crate::message::{
+    MediaBody, MediaFile, MediaId, MediaRange, Message, SendMessage, SentMessage,
+};
 use crate::resp::LoginInfo;
 use crate::resp::ResponseCheckLogin;
 use crate::resp::ResponseSyncCheck;
 use crate::resp::ResponseSyncMessage;
 use crate::resp::ResponseWebInit;
 use crate::resp::SyncKey;
+use crate::resp::{ResponseCreateChatroom, ResponseUpdateChatroom};
 use crate::{
     errors::Error,
     storage::{BaseRequest, WechatDomain},
@@ -33,7 +39,7 @@ impl Caller {
     //     self.path = path;
     // }
 
-    pub fn set_mod(&mut self, mode: Mode) {
+    pub fn set_mod(&self, mode: Mode) {
         self.client.set_mode(mode);
     }
 
@@ -42,18 +48,25 @@ impl Caller {
         self.client.get_login_uuid().await
     }
 
+    /// 向手机推送登录确认, 返回用于检查登录状态的UUID
+    pub async fn web_wx_push_login(&self, uin: i64) -> Result<String, Error> {
+        debug!("caller::web_wx_push_login {uin}");
+        let resp = self.client.web_wx_push_login(uin).await?;
+        Ok(resp.uuid)
+    }
+
     /// 检查是否登录成功
     pub async fn check_login(&self, uuid: &str) -> Result<ResponseCheckLogin, Error> {
         self.client.check_login(uuid).await
     }
 
     /// 获取登录信息
-    pub async fn get_login_info(&mut self, url: &str) -> Result<LoginInfo, Error> {
+    pub async fn get_login_info(&self, url: &str) -> Result<LoginInfo, Error> {
         debug!("caller::get_login_info {}", url);
         self.client.get_login_info(url).await
     }
 
-    pub async fn add_cookies(&mut self, (url, cookie): (String, CookieStore)) {
+    pub async fn add_cookies(&self, (url, cookie): (String, CookieStore)) {
         self.client.add_cookies((url, cookie)).await
     }
 
@@ -61,7 +74,7 @@ impl Caller {
         self.client.get_coookies().await
     }
 
-    pub fn set_domain(&mut self, domain: Option<WechatDomain>) {
+    pub fn set_domain(&self, domain: Option<WechatDomain>) {
         self.client.set_domain(domain);
     }
 
@@ -86,15 +99,21 @@ impl Caller {
             .await
     }
 
+    /// 退出登录
+    pub async fn web_wx_logout(&self, login_info: &LoginInfo) -> Result<(), Error> {
+        debug!("caller::web_wx_logout");
+        self.client.web_wx_logout(login_info).await
+    }
+
     pub async fn sync_check(
         &self,
         device_id: &str,
-        web_init_resp: &ResponseWebInit,
+        sync_key: &SyncKey,
         login_info: &LoginInfo,
     ) -> Result<ResponseSyncCheck, Error> {
         debug!("client::sync_check");
         self.client
-            .sync_check(device_id, web_init_resp, login_info)
+            .sync_check(device_id, sync_key, login_info)
             .await
     }
 
@@ -108,4 +127,178 @@ impl Caller {
             .sync_message(base_req, sync_key, login_info)
             .await
     }
+
+    /// 发送消息
+    pub async fn web_wx_send_msg(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        msg: SendMessage,
+    ) -> Result<SentMessage, Error> {
+        debug!("caller::web_wx_send_msg");
+        let resp = self
+            .client
+            .web_wx_send_msg(base_req, login_info, &msg)
+            .await?;
+        Ok(SentMessage::new(msg, resp.msg_id))
+    }
+
+    /// 撤回消息
+    pub async fn web_wx_revoke_msg(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        msg: &SentMessage,
+    ) -> Result<(), Error> {
+        debug!("caller::web_wx_revoke_msg");
+        self.client
+            .web_wx_revoke_msg(base_req, login_info, msg)
+            .await?;
+        Ok(())
+    }
+
+    /// 创建群聊
+    pub async fn web_wx_create_chatroom(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        topic: &str,
+        user_names: &[&str],
+    ) -> Result<ResponseCreateChatroom, Error> {
+        debug!("caller::web_wx_create_chatroom");
+        self.client
+            .web_wx_create_chatroom(base_req, login_info, topic, user_names)
+            .await
+    }
+
+    /// 修改群聊
+    pub async fn web_wx_update_chatroom(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        chat_room_name: &str,
+        update: &ChatroomUpdate,
+    ) -> Result<ResponseUpdateChatroom, Error> {
+        debug!("caller::web_wx_update_chatroom");
+        self.client
+            .web_wx_update_chatroom(base_req, login_info, chat_room_name, update)
+            .await
+    }
+
+    /// 添加好友或者通过好友请求
+    pub async fn web_wx_verify_user(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        opcode: VerifyOpcode,
+        user_name: &str,
+        ticket: &str,
+        verify_content: &str,
+    ) -> Result<(), Error> {
+        debug!("caller::web_wx_verify_user");
+        self.client
+            .web_wx_verify_user(
+                base_req,
+                login_info,
+                opcode,
+                user_name,
+                ticket,
+                verify_content,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 上传媒体文件, 已经上传过的文件直接返回MediaId
+    pub async fn upload_media(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        file: &MediaFile,
+        from_user_name: &str,
+        to_user_name: &str,
+    ) -> Result<MediaId, Error> {
+        debug!("caller::upload_media {}", file.name());
+        let check = self
+            .client
+            .web_wx_check_upload(base_req, file, from_user_name, to_user_name)
+            .await?;
+        if !check.media_id.is_empty() {
+            debug!("文件{}已上传过", file.name());
+            return Ok(MediaId::from(check.media_id));
+        }
+
+        let resp = self
+            .client
+            .web_wx_upload_media(
+                base_req,
+                login_info,
+                file,
+                from_user_name,
+                to_user_name,
+                &check,
+            )
+            .await?;
+        if resp.media_id.is_empty() {
+            return Err(Error::UploadMedia(format!(
+                "上传文件{}没有返回MediaId",
+                file.name()
+            )));
+        }
+        Ok(MediaId::from(resp.media_id))
+    }
+
+    /// 下载消息中的媒体文件
+    pub async fn download_media(
+        &self,
+        msg: &Message,
+        login_info: &LoginInfo,
+        thumbnail: bool,
+        range: Option<MediaRange>,
+    ) -> Result<MediaBody, Error> {
+        debug!("caller::download_media {}", msg.msg_id);
+        let resp = self
+            .client
+            .web_wx_get_media(msg, login_info, thumbnail, range)
+            .await?;
+        Ok(MediaBody::new(resp))
+    }
+
+    /// 获取全部联系人, 根据Seq游标分页直到返回0
+    pub async fn web_wx_get_contact(&self, login_info: &LoginInfo) -> Result<Vec<Contact>, Error> {
+        debug!("caller::web_wx_get_contact");
+        let mut contacts = Vec::new();
+        let mut seq = 0;
+        loop {
+            let resp = self.client.web_wx_get_contact(login_info, seq).await?;
+            contacts.extend(resp.member_list);
+            if resp.seq == 0 {
+                break;
+            }
+            seq = resp.seq;
+        }
+        Ok(contacts)
+    }
+
+    /// 批量获取联系人详情, 按服务端单次上限分批请求
+    pub async fn web_wx_batch_get_contact(
+        &self,
+        base_req: &BaseRequest,
+        login_info: &LoginInfo,
+        user_names: &[UserName],
+    ) -> Result<Vec<Contact>, Error> {
+        debug!("caller::web_wx_batch_get_contact");
+        let mut contacts = Vec::with_capacity(user_names.len());
+        for chunk in user_names.chunks(MAX_BATCH_GET_CONTACT) {
+            let resp = self
+                .client
+                .web_wx_batch_get_contact(base_req, login_info, chunk)
+                .await?;
+            contacts.extend(resp.contact_list);
+        }
+        Ok(contacts)
+    }
 }
+
+/// webwxbatchgetcontact单次最多查询的联系人数量
+const MAX_BATCH_GET_CONTACT: usize = 50;
@@ -0,0 +1,156 @@
+use super::{Contact, Friend, Group, Mp};
+
+/// 联系人集合
+#[derive(Debug, Clone, Default)]
+pub struct Contacts(Vec<Contact>);
+
+impl Contacts {
+    pub fn new(contacts: Vec<Contact>) -> Self {
+        Self(contacts)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Contact> {
+        self.0.iter()
+    }
+
+    pub fn get(&self, user_name: &str) -> Option<&Contact> {
+        self.0.iter().find(|c| c.user_name == user_name)
+    }
+
+    pub fn friends(&self) -> Vec<Friend> {
+        self.0
+            .iter()
+            .filter(|c| c.is_friend())
+            .cloned()
+            .map(Friend::new)
+            .collect()
+    }
+
+    pub fn groups(&self) -> Vec<Group> {
+        self.0
+            .iter()
+            .filter(|c| c.is_group())
+            .cloned()
+            .map(Group::new)
+            .collect()
+    }
+
+    pub fn mps(&self) -> Vec<Mp> {
+        self.0
+            .iter()
+            .filter(|c| c.is_mp())
+            .cloned()
+            .map(Mp::new)
+            .collect()
+    }
+
+    /// 昵称、备注名、微信号或者全拼中包含关键字的联系人
+    pub fn search(&self, keyword: &str) -> Contacts {
+        self.filter(|c| c.matches(keyword))
+    }
+
+    pub fn search_by_nick_name(&self, nick_name: &str) -> Contacts {
+        self.filter(|c| c.nick_name == nick_name)
+    }
+
+    pub fn search_by_remark_name(&self, remark_name: &str) -> Contacts {
+        self.filter(|c| c.remark_name == remark_name)
+    }
+
+    pub fn search_by_alias(&self, alias: &str) -> Contacts {
+        self.filter(|c| c.alias == alias)
+    }
+
+    /// 根据昵称或者备注名的全拼查找, 不区分大小写
+    pub fn search_by_pinyin(&self, pinyin: &str) -> Contacts {
+        self.filter(|c| {
+            c.py_quan_pin.eq_ignore_ascii_case(pinyin)
+                || c.remark_pyquan_pin.eq_ignore_ascii_case(pinyin)
+        })
+    }
+
+    /// 合并联系人, 已存在的根据UserName覆盖
+    pub fn merge(&mut self, contacts: Vec<Contact>) {
+        for contact in contacts {
+            match self.0.iter_mut().find(|c| c.user_name == contact.user_name) {
+                Some(c) => *c = contact,
+                None => self.0.push(contact),
+            }
+        }
+    }
+
+    fn filter(&self, f: impl Fn(&Contact) -> bool) -> Contacts {
+        Contacts(self.0.iter().filter(|c| f(c)).cloned().collect())
+    }
+}
+
+impl From<Vec<Contact>> for Contacts {
+    fn from(value: Vec<Contact>) -> Self {
+        Self(value)
+    }
+}
+
+impl IntoIterator for Contacts {
+    type Item = Contact;
+    type IntoIter = std::vec::IntoIter<Contact>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(user_name: &str, nick_name: &str, py_quan_pin: &str, verify_flag: i32) -> Contact {
+        Contact {
+            user_name: user_name.to_string(),
+            nick_name: nick_name.to_string(),
+            py_quan_pin: py_quan_pin.to_string(),
+            verify_flag,
+            contact_flag: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_contacts() {
+        let contacts = Contacts::new(vec![
+            contact("@a", "张三", "zhangsan", 0),
+            contact("@@g", "测试群", "ceshiqun", 0),
+            contact("@mp", "公众号", "gongzhonghao", 8),
+            contact("filehelper", "文件传输助手", "wenjianchuanshuzhushou", 0),
+            Contact {
+                user_name: "@stranger".to_string(),
+                ..Default::default()
+            },
+        ]);
+
+        assert_eq!(contacts.friends().len(), 1);
+        assert_eq!(contacts.groups().len(), 1);
+        assert_eq!(contacts.mps().len(), 1);
+        assert_eq!(contacts.search_by_pinyin("ZhangSan").len(), 1);
+        assert_eq!(contacts.search("ceshi").groups()[0].user_name, "@@g");
+        assert!(contacts.get("@mp").unwrap().is_mp());
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut contacts = Contacts::new(vec![contact("@a", "张三", "zhangsan", 0)]);
+        contacts.merge(vec![
+            contact("@a", "张三丰", "zhangsanfeng", 0),
+            contact("@b", "李四", "lisi", 0),
+        ]);
+        assert_eq!(contacts.len(), 2);
+        assert_eq!(contacts.get("@a").unwrap().nick_name, "张三丰");
+    }
+}
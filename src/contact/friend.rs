@@ -0,0 +1,25 @@
+use std::ops::Deref;
+
+use super::Contact;
+
+/// 好友
+#[derive(Debug, Clone)]
+pub struct Friend(Contact);
+
+impl Friend {
+    pub(crate) fn new(contact: Contact) -> Self {
+        Self(contact)
+    }
+
+    pub fn into_contact(self) -> Contact {
+        self.0
+    }
+}
+
+impl Deref for Friend {
+    type Target = Contact;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,105 @@
+use std::ops::Deref;
+
+use crate::{bot::Bot, errors::Error};
+
+use super::{Contact, Member, UserName};
+
+/// 群组
+#[derive(Debug, Clone)]
+pub struct Group(Contact);
+
+impl Group {
+    pub(crate) fn new(contact: Contact) -> Self {
+        Self(contact)
+    }
+
+    pub fn into_contact(self) -> Contact {
+        self.0
+    }
+
+    pub fn members(&self) -> &[Member] {
+        &self.0.member_list
+    }
+
+    pub fn member(&self, user_name: &str) -> Option<&Member> {
+        self.0.member_list.iter().find(|m| m.user_name == user_name)
+    }
+
+    /// 用于批量获取群成员详情的查询条件
+    pub fn member_user_names(&self) -> Vec<UserName> {
+        self.0
+            .member_list
+            .iter()
+            .map(|m| UserName::with_chat_room(&m.user_name, &self.0.encry_chat_room_id))
+            .collect()
+    }
+
+    /// 根据群昵称或者昵称查找群成员
+    pub fn search_members(&self, name: &str) -> Vec<&Member> {
+        self.0
+            .member_list
+            .iter()
+            .filter(|m| m.display_name == name || m.nick_name == name)
+            .collect()
+    }
+
+    /// 拉好友进群, 成功后更新为最新的群成员列表
+    pub async fn add_members<S>(
+        &mut self,
+        bot: &Bot<S>,
+        user_names: &[&str],
+    ) -> Result<&[Member], Error>
+    where
+        S: Send + Sync + 'static,
+    {
+        *self = bot.add_group_members(&self.0.user_name, user_names).await?;
+        Ok(self.members())
+    }
+
+    /// 移出群成员, 需要是群主, 成功后更新为最新的群成员列表
+    pub async fn remove_members<S>(
+        &mut self,
+        bot: &Bot<S>,
+        user_names: &[&str],
+    ) -> Result<&[Member], Error>
+    where
+        S: Send + Sync + 'static,
+    {
+        *self = bot
+            .remove_group_members(&self.0.user_name, user_names)
+            .await?;
+        Ok(self.members())
+    }
+
+    /// 发送入群邀请, 对方同意后才会出现在群成员列表中
+    pub async fn invite_members<S>(
+        &mut self,
+        bot: &Bot<S>,
+        user_names: &[&str],
+    ) -> Result<&[Member], Error>
+    where
+        S: Send + Sync + 'static,
+    {
+        *self = bot
+            .invite_group_members(&self.0.user_name, user_names)
+            .await?;
+        Ok(self.members())
+    }
+
+    /// 修改群名称
+    pub async fn rename<S>(&mut self, bot: &Bot<S>, topic: &str) -> Result<(), Error>
+    where
+        S: Send + Sync + 'static,
+    {
+        *self = bot.rename_group(&self.0.user_name, topic).await?;
+        Ok(())
+    }
+}
+
+impl Deref for Group {
+    type Target = Contact;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+
+use crate::consts::{CONTACT_FLAG_CONTACT, SPECIAL_USER_NAMES};
+
+pub use contacts::Contacts;
+pub use friend::Friend;
+pub use group::Group;
+pub use mp::Mp;
+pub use store::{ContactEvent, ContactStore};
+
+mod contacts;
+mod friend;
+mod group;
+mod mp;
+mod store;
+
+/// 联系人, 包括好友、群组以及公众号
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Contact {
+    #[serde(rename = "Uin")]
+    pub uin: i64,
+    #[serde(rename = "UserName")]
+    pub user_name: String,
+    #[serde(rename = "NickName")]
+    pub nick_name: String,
+    #[serde(rename = "HeadImgUrl")]
+    pub head_img_url: String,
+    #[serde(rename = "ContactFlag")]
+    pub contact_flag: i32,
+    #[serde(rename = "MemberCount")]
+    pub member_count: i32,
+    /// 群成员列表, 只有群组才有
+    #[serde(rename = "MemberList")]
+    pub member_list: Vec<Member>,
+    #[serde(rename = "RemarkName")]
+    pub remark_name: String,
+    #[serde(rename = "HideInputBarFlag")]
+    pub hide_input_bar_flag: i32,
+    #[serde(rename = "Sex")]
+    pub sex: i32,
+    #[serde(rename = "Signature")]
+    pub signature: String,
+    #[serde(rename = "VerifyFlag")]
+    pub verify_flag: i32,
+    #[serde(rename = "OwnerUin")]
+    pub owner_uin: i64,
+    #[serde(rename = "PYInitial")]
+    pub py_initial: String,
+    #[serde(rename = "PYQuanPin")]
+    pub py_quan_pin: String,
+    #[serde(rename = "RemarkPYInitial")]
+    pub remark_pyinitial: String,
+    #[serde(rename = "RemarkPYQuanPin")]
+    pub remark_pyquan_pin: String,
+    #[serde(rename = "StarFriend")]
+    pub star_friend: i32,
+    #[serde(rename = "AppAccountFlag")]
+    pub app_account_flag: i32,
+    #[serde(rename = "Statues")]
+    pub statues: i32,
+    #[serde(rename = "AttrStatus")]
+    pub attr_status: i64,
+    #[serde(rename = "Province")]
+    pub province: String,
+    #[serde(rename = "City")]
+    pub city: String,
+    #[serde(rename = "Alias")]
+    pub alias: String,
+    #[serde(rename = "SnsFlag")]
+    pub sns_flag: i32,
+    #[serde(rename = "UniFriend")]
+    pub uni_friend: i32,
+    #[serde(rename = "DisplayName")]
+    pub display_name: String,
+    #[serde(rename = "ChatRoomId")]
+    pub chat_room_id: i64,
+    #[serde(rename = "KeyWord")]
+    pub key_word: String,
+    /// 获取群成员详情时需要的加密群ID
+    #[serde(rename = "EncryChatRoomId")]
+    pub encry_chat_room_id: String,
+    #[serde(rename = "IsOwner")]
+    pub is_owner: i32,
+    #[serde(rename = "WebWxPluginSwitch")]
+    pub web_wx_plugin_switch: i32,
+    #[serde(rename = "HeadImgFlag")]
+    pub head_img_flag: i32,
+}
+
+impl Contact {
+    /// 群组的UserName以@@开头
+    pub fn is_group(&self) -> bool {
+        self.user_name.starts_with("@@")
+    }
+
+    /// 公众号的VerifyFlag包含8
+    pub fn is_mp(&self) -> bool {
+        self.verify_flag & 8 != 0
+    }
+
+    /// 文件传输助手、微信团队等内置账号
+    pub fn is_special(&self) -> bool {
+        SPECIAL_USER_NAMES.contains(&self.user_name.as_str())
+    }
+
+    /// 通讯录中的好友, 群成员详情等接口返回的陌生人没有通讯录标记
+    pub fn is_friend(&self) -> bool {
+        self.contact_flag & CONTACT_FLAG_CONTACT != 0
+            && !self.is_group()
+            && !self.is_mp()
+            && !self.is_special()
+    }
+
+    /// 有备注名时使用备注名, 否则使用昵称
+    pub fn display(&self) -> &str {
+        if self.remark_name.is_empty() {
+            &self.nick_name
+        } else {
+            &self.remark_name
+        }
+    }
+
+    /// 昵称、备注名、微信号或者全拼中是否包含关键字, 不区分大小写
+    pub fn matches(&self, keyword: &str) -> bool {
+        let keyword = keyword.to_lowercase();
+        [
+            &self.nick_name,
+            &self.remark_name,
+            &self.alias,
+            &self.py_quan_pin,
+            &self.remark_pyquan_pin,
+        ]
+        .iter()
+        .any(|field| field.to_lowercase().contains(&keyword))
+    }
+}
+
+/// 批量获取联系人时的查询条件
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserName {
+    #[serde(rename = "UserName")]
+    pub user_name: String,
+    /// 查询群成员详情时填写所在群的EncryChatRoomId
+    #[serde(rename = "EncryChatRoomId")]
+    pub encry_chat_room_id: String,
+}
+
+impl UserName {
+    pub fn new(user_name: impl Into<String>) -> Self {
+        Self {
+            user_name: user_name.into(),
+            encry_chat_room_id: String::new(),
+        }
+    }
+
+    pub fn with_chat_room(
+        user_name: impl Into<String>,
+        encry_chat_room_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            user_name: user_name.into(),
+            encry_chat_room_id: encry_chat_room_id.into(),
+        }
+    }
+}
+
+impl From<&str> for UserName {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for UserName {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+/// 群成员
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Member {
+    #[serde(rename = "Uin")]
+    pub uin: i64,
+    #[serde(rename = "UserName")]
+    pub user_name: String,
+    #[serde(rename = "NickName")]
+    pub nick_name: String,
+    #[serde(rename = "AttrStatus")]
+    pub attr_status: i64,
+    #[serde(rename = "PYInitial")]
+    pub py_initial: String,
+    #[serde(rename = "PYQuanPin")]
+    pub py_quan_pin: String,
+    #[serde(rename = "RemarkPYInitial")]
+    pub remark_pyinitial: String,
+    #[serde(rename = "RemarkPYQuanPin")]
+    pub remark_pyquan_pin: String,
+    #[serde(rename = "MemberStatus")]
+    pub member_status: i32,
+    /// 群昵称
+    #[serde(rename = "DisplayName")]
+    pub display_name: String,
+    #[serde(rename = "KeyWord")]
+    pub key_word: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_contact() {
+        let json = r#"{"Uin":0,"UserName":"@@abc","NickName":"测试群","HeadImgUrl":"/cgi-bin/mmwebwx-bin/webwxgetheadimg?seq=0&username=@@abc","ContactFlag":2,"MemberCount":2,"MemberList":[{"Uin":0,"UserName":"@m1","NickName":"张三","AttrStatus":0,"PYInitial":"","PYQuanPin":"","RemarkPYInitial":"","RemarkPYQuanPin":"","MemberStatus":0,"DisplayName":"三哥","KeyWord":""}],"RemarkName":"","HideInputBarFlag":0,"Sex":0,"Signature":"","VerifyFlag":0,"OwnerUin":0,"PYInitial":"CSQ","PYQuanPin":"ceshiqun","RemarkPYInitial":"","RemarkPYQuanPin":"","StarFriend":0,"AppAccountFlag":0,"Statues":0,"AttrStatus":0,"Province":"","City":"","Alias":"","SnsFlag":0,"UniFriend":0,"DisplayName":"","ChatRoomId":0,"KeyWord":"","EncryChatRoomId":"@enc","IsOwner":1}"#;
+        let contact: Contact = serde_json::from_str(json).unwrap();
+        assert_eq!(contact.user_name, "@@abc");
+        assert_eq!(contact.member_list.len(), 1);
+        assert_eq!(contact.member_list[0].display_name, "三哥");
+        assert_eq!(contact.encry_chat_room_id, "@enc");
+        assert_eq!(contact.web_wx_plugin_switch, 0);
+        assert!(contact.is_group());
+        assert!(!contact.is_friend());
+    }
+
+    #[test]
+    fn test_classify_contact() {
+        let mp = Contact {
+            user_name: "@mp".to_string(),
+            verify_flag: 24,
+            ..Default::default()
+        };
+        assert!(mp.is_mp());
+        assert!(!mp.is_friend());
+
+        let helper = Contact {
+            user_name: "filehelper".to_string(),
+            ..Default::default()
+        };
+        assert!(helper.is_special());
+        assert!(!helper.is_friend());
+
+        let stranger = Contact {
+            user_name: "@stranger".to_string(),
+            ..Default::default()
+        };
+        assert!(!stranger.is_friend());
+
+        let friend = Contact {
+            user_name: "@friend".to_string(),
+            contact_flag: 3,
+            nick_name: "张三".to_string(),
+            remark_name: "老张".to_string(),
+            remark_pyquan_pin: "laozhang".to_string(),
+            ..Default::default()
+        };
+        assert!(friend.is_friend());
+        assert_eq!(friend.display(), "老张");
+        assert!(friend.matches("LaoZ"));
+        assert!(friend.matches("张三"));
+        assert!(!friend.matches("李四"));
+    }
+}
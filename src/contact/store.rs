@@ -0,0 +1,252 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use tokio::sync::broadcast;
+
+use crate::resp::ResponseSyncMessage;
+
+use super::{Contact, Contacts, Member};
+
+/// 事件通道的缓冲大小, 订阅者处理过慢时会丢弃最旧的事件
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// 联系人变更事件
+#[derive(Debug, Clone)]
+pub enum ContactEvent {
+    /// 新增联系人
+    Added(Contact),
+    /// 联系人信息变更
+    Modified(Contact),
+    /// 删除联系人
+    Removed(Contact),
+    /// 群成员变更
+    GroupMembersChanged {
+        group_user_name: String,
+        joined: Vec<Member>,
+        left: Vec<Member>,
+    },
+}
+
+/// 联系人缓存, 登录和获取联系人时整体加载, 之后根据同步消息增量更新
+#[derive(Debug, Clone)]
+pub struct ContactStore {
+    contacts: Arc<RwLock<HashMap<String, Contact>>>,
+    events: broadcast::Sender<ContactEvent>,
+}
+
+impl Default for ContactStore {
+    fn default() -> Self {
+        Self {
+            contacts: Default::default(),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+        }
+    }
+}
+
+impl ContactStore {
+    /// 订阅联系人变更事件
+    pub fn subscribe(&self) -> broadcast::Receiver<ContactEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn get(&self, user_name: &str) -> Option<Contact> {
+        self.contacts
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(user_name)
+            .cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.contacts
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 当前缓存的全部联系人
+    pub fn snapshot(&self) -> Contacts {
+        let contacts = self.contacts.read().unwrap_or_else(PoisonError::into_inner);
+        Contacts::new(contacts.values().cloned().collect())
+    }
+
+    /// 整体加载联系人, 不产生变更事件
+    pub(crate) fn load(&self, contacts: Vec<Contact>) {
+        let mut cache = self
+            .contacts
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        for contact in contacts {
+            let contact = keep_members(cache.get(&contact.user_name), contact);
+            cache.insert(contact.user_name.clone(), contact);
+        }
+    }
+
+    /// 新增或者更新联系人
+    pub(crate) fn upsert(&self, contact: Contact) {
+        let (old, new) = {
+            let mut cache = self
+                .contacts
+                .write()
+                .unwrap_or_else(PoisonError::into_inner);
+            let new = keep_members(cache.get(&contact.user_name), contact);
+            let old = cache.insert(new.user_name.clone(), new.clone());
+            (old, new)
+        };
+
+        match old {
+            None => self.emit(ContactEvent::Added(new)),
+            Some(old) => {
+                self.emit_member_changes(&old, &new);
+                self.emit(ContactEvent::Modified(new));
+            }
+        }
+    }
+
+    pub(crate) fn remove(&self, user_name: &str) {
+        let old = self
+            .contacts
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(user_name);
+        if let Some(old) = old {
+            self.emit(ContactEvent::Removed(old));
+        }
+    }
+
+    /// 根据同步消息中的ModContactList、DelContactList和ModChatRoomMemberList更新缓存
+    pub(crate) fn apply_sync(&self, resp: &ResponseSyncMessage) {
+        for contact in resp.mod_contact_list.iter() {
+            self.upsert(contact.clone());
+        }
+        for contact in resp.del_contact_list.iter() {
+            self.remove(&contact.user_name);
+        }
+        for group in resp.mod_chat_room_member_list.iter() {
+            self.upsert(group.clone());
+        }
+    }
+
+    fn emit_member_changes(&self, old: &Contact, new: &Contact) {
+        if !new.is_group() || old.member_list.is_empty() {
+            return;
+        }
+
+        let old_names = old
+            .member_list
+            .iter()
+            .map(|m| m.user_name.as_str())
+            .collect::<HashSet<_>>();
+        let new_names = new
+            .member_list
+            .iter()
+            .map(|m| m.user_name.as_str())
+            .collect::<HashSet<_>>();
+
+        let joined = new
+            .member_list
+            .iter()
+            .filter(|m| !old_names.contains(m.user_name.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        let left = old
+            .member_list
+            .iter()
+            .filter(|m| !new_names.contains(m.user_name.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if !joined.is_empty() || !left.is_empty() {
+            self.emit(ContactEvent::GroupMembersChanged {
+                group_user_name: new.user_name.clone(),
+                joined,
+                left,
+            });
+        }
+    }
+
+    fn emit(&self, event: ContactEvent) {
+        // 没有订阅者时发送失败, 直接忽略
+        let _ = self.events.send(event);
+    }
+}
+
+/// 部分接口返回的群组不带成员列表, 此时保留缓存中的成员
+fn keep_members(old: Option<&Contact>, mut new: Contact) -> Contact {
+    if let Some(old) = old {
+        if new.member_list.is_empty() && !old.member_list.is_empty() {
+            new.member_list = old.member_list.clone();
+            new.member_count = old.member_count;
+        }
+    }
+    new
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(user_name: &str) -> Member {
+        Member {
+            user_name: user_name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn group(members: Vec<Member>) -> Contact {
+        Contact {
+            user_name: "@@g".to_string(),
+            member_list: members,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_store_events() {
+        let store = ContactStore::default();
+        store.load(vec![group(vec![member("@a"), member("@b")])]);
+        let mut events = store.subscribe();
+
+        store.upsert(group(vec![member("@b"), member("@c")]));
+        match events.try_recv().unwrap() {
+            ContactEvent::GroupMembersChanged { joined, left, .. } => {
+                assert_eq!(joined[0].user_name, "@c");
+                assert_eq!(left[0].user_name, "@a");
+            }
+            e => panic!("unexpected event: {e:?}"),
+        }
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            ContactEvent::Modified(_)
+        ));
+
+        // 不带成员列表的更新保留原有成员
+        store.upsert(group(vec![]));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            ContactEvent::Modified(_)
+        ));
+        assert_eq!(store.get("@@g").unwrap().member_list.len(), 2);
+
+        store.upsert(Contact {
+            user_name: "@d".to_string(),
+            ..Default::default()
+        });
+        assert!(matches!(events.try_recv().unwrap(), ContactEvent::Added(_)));
+
+        store.remove("@d");
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            ContactEvent::Removed(_)
+        ));
+        store.remove("@d");
+        assert!(events.try_recv().is_err());
+        assert_eq!(store.len(), 1);
+    }
+}
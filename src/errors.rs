@@ -8,6 +8,8 @@ pub enum Error {
     StatusUnknown(String),
     #[error("Login timeout")]
     LoginTimeout,
+    #[error("Login deadline exceeded")]
+    LoginDeadlineExceeded,
     #[error("GetLoginInfo error: {0}")]
     GetLoginInfo(String),
     #[error("Reqwest error: {0}")]
@@ -26,12 +28,57 @@ pub enum Error {
     WebInit(String),
     #[error("No base request")]
     NoBaseRequest,
+    #[error("Not logged in")]
+    NotLoggedIn,
     #[error("StatusNotify error: {0}")]
     StatusNotify(String),
     #[error("SyncCheck error: {0}")]
     SyncCheck(String),
+    #[error("SyncCheck retcode: {0}")]
+    SyncCheckRetCode(crate::resp::RetCode),
     #[error("Sync error: {0}")]
     Sync(String),
+    #[error("SendMessage error: {0}")]
+    SendMessage(String),
+    #[error("SendMessage request failed: {ret:?} {errmsg}")]
+    SendMessageRet {
+        ret: crate::resp::Ret,
+        errmsg: String,
+    },
+    #[error("Forward error after {} sent: {source}", sent.len())]
+    Forward {
+        /// 出错前已经转发成功的消息
+        sent: Vec<crate::message::SentMessage>,
+        source: Box<Error>,
+    },
+    #[error("UploadMedia error: {0}")]
+    UploadMedia(String),
+    #[error("DownloadMedia error: {0}")]
+    DownloadMedia(String),
+    #[error("GetContact error: {0}")]
+    GetContact(String),
+    #[error("RevokeMessage error: {0}")]
+    RevokeMessage(String),
+    #[error("Chatroom error: {0}")]
+    Chatroom(String),
+    #[error("Chatroom request failed: {ret:?} {errmsg}")]
+    ChatroomRet {
+        ret: crate::resp::Ret,
+        errmsg: String,
+    },
+    #[error("VerifyUser error: {0}")]
+    VerifyUser(String),
+    #[error("VerifyUser request failed: {ret:?} {errmsg}")]
+    VerifyUserRet {
+        ret: crate::resp::Ret,
+        errmsg: String,
+    },
+    #[error("QrCode error: {0}")]
+    QrCode(String),
+    #[error("PushLogin error: {0}")]
+    PushLogin(String),
+    #[error("Logout error: {0}")]
+    Logout(String),
     #[error("OpenFile error: {0}")]
     OpenFile(String),
 }
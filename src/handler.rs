@@ -0,0 +1,78 @@
+use std::{future::Future, ops::Deref, pin::Pin, sync::Arc};
+
+/// 回调返回的Future
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// 异步回调, 接收[`Ctx`]并返回Future
+pub type Handler<S, T, R = ()> = Arc<dyn Fn(Ctx<S, T>) -> BoxFuture<'static, R> + Send + Sync>;
+
+/// 回调的上下文, 包含用户共享的状态和本次事件的数据
+pub struct Ctx<S, T> {
+    state: Arc<S>,
+    data: T,
+}
+
+impl<S, T> Ctx<S, T> {
+    pub(crate) fn new(state: Arc<S>, data: T) -> Self {
+        Self { state, data }
+    }
+
+    /// 创建Bot时传入的共享状态
+    pub fn state(&self) -> &Arc<S> {
+        &self.state
+    }
+
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    pub fn into_data(self) -> T {
+        self.data
+    }
+}
+
+impl<S, T: Clone> Clone for Ctx<S, T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+impl<S, T> Deref for Ctx<S, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+/// 把异步闭包包装成[`Handler`]
+pub fn handler_fn<S, T, R, F, Fut>(f: F) -> Handler<S, T, R>
+where
+    F: Fn(Ctx<S, T>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = R> + Send + 'static,
+{
+    Arc::new(move |ctx| Box::pin(f(ctx)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_handler_fn() {
+        let handler: Handler<AtomicUsize, usize> =
+            handler_fn(|ctx: Ctx<AtomicUsize, usize>| async move {
+                ctx.state().fetch_add(*ctx, Ordering::SeqCst);
+            });
+
+        let state = Arc::new(AtomicUsize::new(0));
+        handler(Ctx::new(state.clone(), 2)).await;
+        handler(Ctx::new(state.clone(), 3)).await;
+        assert_eq!(state.load(Ordering::SeqCst), 5);
+    }
+}
@@ -3,9 +3,13 @@ pub mod bot;
 
 mod caller;
 mod consts;
+pub mod contact;
 mod errors;
-mod message;
+pub mod handler;
+pub mod login_qrcode;
+pub mod message;
 mod resp;
-mod storage;
+pub mod storage;
 
 pub use errors::Error;
+pub use resp::Ret;
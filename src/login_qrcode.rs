@@ -0,0 +1,218 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
+
+use log::{info, warn};
+use qrcode::{
+    render::{svg, unicode::Dense1x2},
+    Color, QrCode,
+};
+
+use crate::{
+    consts::{QRCODE, QRCODE_LOGIN},
+    errors::Error,
+    handler::{BoxFuture, Ctx},
+};
+
+/// PNG图片中每个模块的像素大小
+const PNG_MODULE_SIZE: usize = 8;
+/// 二维码四周的空白模块数量
+const QUIET_ZONE: usize = 4;
+
+/// 登录二维码的输出方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QrOutput {
+    /// 使用Unicode半角方块打印到终端, 适用于无图形界面的服务器
+    ///
+    /// 默认使用方块字符绘制暗色模块, 适用于浅色背景的终端, 深色背景的终端设置`invert`反色显示
+    Terminal { invert: bool },
+    /// 保存为PNG图片
+    Png(PathBuf),
+    /// 保存为SVG图片
+    Svg(PathBuf),
+    /// 使用系统默认程序打开二维码网址
+    Open,
+}
+
+/// 在本地根据UUID生成的登录二维码
+pub struct LoginQrCode {
+    uuid: String,
+    code: QrCode,
+}
+
+impl LoginQrCode {
+    pub fn new(uuid: &str) -> Result<Self, Error> {
+        let code = QrCode::new(format!("{QRCODE_LOGIN}{uuid}"))
+            .map_err(|e| Error::QrCode(format!("生成二维码失败: {e}")))?;
+        Ok(Self {
+            uuid: uuid.to_owned(),
+            code,
+        })
+    }
+
+    /// 二维码的内容, 使用微信扫描该网址登录
+    pub fn login_url(&self) -> String {
+        format!("{QRCODE_LOGIN}{}", self.uuid)
+    }
+
+    /// 微信服务器生成的二维码图片网址
+    pub fn image_url(&self) -> String {
+        format!("{QRCODE}{}", self.uuid)
+    }
+
+    /// 渲染为终端文本, `invert`为true时使用方块字符绘制亮色模块, 适用于深色背景的终端
+    pub fn to_terminal_string(&self, invert: bool) -> String {
+        let (dark, light) = if invert {
+            (Dense1x2::Light, Dense1x2::Dark)
+        } else {
+            (Dense1x2::Dark, Dense1x2::Light)
+        };
+        self.code
+            .render::<Dense1x2>()
+            .dark_color(dark)
+            .light_color(light)
+            .quiet_zone(true)
+            .build()
+    }
+
+    pub fn to_svg(&self) -> String {
+        self.code
+            .render::<svg::Color>()
+            .min_dimensions(256, 256)
+            .build()
+    }
+
+    /// 编码为8位灰度PNG图片
+    pub fn to_png(&self) -> Result<Vec<u8>, Error> {
+        let width = self.code.width();
+        let colors = self.code.to_colors();
+        let size = (width + QUIET_ZONE * 2) * PNG_MODULE_SIZE;
+
+        let mut pixels = vec![u8::MAX; size * size];
+        for (i, color) in colors.iter().enumerate() {
+            if *color != Color::Dark {
+                continue;
+            }
+            let x = (i % width + QUIET_ZONE) * PNG_MODULE_SIZE;
+            let y = (i / width + QUIET_ZONE) * PNG_MODULE_SIZE;
+            for row in y..y + PNG_MODULE_SIZE {
+                pixels[row * size + x..row * size + x + PNG_MODULE_SIZE].fill(0);
+            }
+        }
+
+        let mut buf = Vec::new();
+        let mut encoder = png::Encoder::new(&mut buf, size as u32, size as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&pixels))
+            .map_err(|e| Error::QrCode(format!("编码PNG失败: {e}")))?;
+        Ok(buf)
+    }
+
+    /// 按指定方式输出二维码
+    pub async fn output(&self, output: &QrOutput) -> Result<(), Error> {
+        match output {
+            QrOutput::Terminal { invert } => {
+                println!("{}", self.to_terminal_string(*invert));
+                Ok(())
+            }
+            QrOutput::Png(path) => {
+                let data = self.to_png()?;
+                write_file(path, data).await
+            }
+            QrOutput::Svg(path) => write_file(path, self.to_svg().into_bytes()).await,
+            QrOutput::Open => open_url(&self.image_url()),
+        }
+    }
+}
+
+async fn write_file(path: &Path, data: Vec<u8>) -> Result<(), Error> {
+    tokio::fs::write(path, data)
+        .await
+        .map_err(|e| Error::QrCode(format!("保存二维码到{}失败: {e}", path.display())))?;
+    info!("二维码已保存到{}", path.display());
+    Ok(())
+}
+
+fn open_url(url: &str) -> Result<(), Error> {
+    let (command, args): (&str, Vec<&str>) = match std::env::consts::OS {
+        "macos" => ("open", vec![url]),
+        "windows" => ("cmd", vec!["/c", "start", url]),
+        "linux" => ("xdg-open", vec![url]),
+        os => return Err(Error::QrCode(format!("未支持当前操作系统: {os}"))),
+    };
+
+    let res = Command::new(command)
+        .args(args)
+        .output()
+        .map_err(|e| Error::QrCode(format!("执行{command}失败: {e}")))?;
+    if !res.status.success() {
+        return Err(Error::QrCode(format!(
+            "打开二维码失败: {}",
+            String::from_utf8_lossy(&res.stderr)
+        )));
+    }
+    Ok(())
+}
+
+/// 生成UUID回调, 按顺序输出登录二维码, 通过[`Bot::set_uuid_callback`](crate::bot::Bot::set_uuid_callback)安装
+///
+/// 输出失败时只记录日志, 不会中断登录
+pub fn qrcode_callback<S>(
+    outputs: impl IntoIterator<Item = QrOutput>,
+) -> impl Fn(Ctx<S, String>) -> BoxFuture<'static, ()> + Send + Sync + 'static
+where
+    S: Send + Sync + 'static,
+{
+    let outputs: Arc<[QrOutput]> = outputs.into_iter().collect();
+    move |ctx| {
+        let outputs = outputs.clone();
+        Box::pin(async move {
+            let qrcode = match LoginQrCode::new(ctx.data()) {
+                Ok(qrcode) => qrcode,
+                Err(e) => {
+                    warn!("{e}");
+                    return;
+                }
+            };
+            info!("使用微信扫描二维码登录: {}", qrcode.image_url());
+            for output in outputs.iter() {
+                if let Err(e) = qrcode.output(output).await {
+                    warn!("输出登录二维码失败: {e}");
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let qrcode = LoginQrCode::new("gZ_7rsXbLw==").unwrap();
+        assert_eq!(
+            qrcode.login_url(),
+            "https://login.weixin.qq.com/l/gZ_7rsXbLw=="
+        );
+
+        let text = qrcode.to_terminal_string(false);
+        let size = qrcode.code.width() + QUIET_ZONE * 2;
+        assert_eq!(text.lines().count(), size.div_ceil(2));
+        assert!(text.lines().all(|line| line.chars().count() == size));
+        // 四周的空白区域不绘制方块, 反色时相反
+        assert!(text.lines().next().unwrap().chars().all(|c| c == ' '));
+        let inverted = qrcode.to_terminal_string(true);
+        assert!(inverted.lines().next().unwrap().chars().all(|c| c == '█'));
+
+        let png = qrcode.to_png().unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        assert!(qrcode.to_svg().contains("<svg"));
+    }
+}
@@ -0,0 +1,166 @@
+use std::{ops::Deref, time::Duration};
+
+use crate::{
+    bot::Session,
+    contact::{Contact, Group},
+    errors::Error,
+};
+
+use super::{MediaFile, Message, SentMessage};
+
+/// 传给消息handler的上下文, 可以直接回复、转发和撤回消息
+#[derive(Clone)]
+pub struct MessageContext {
+    msg: Message,
+    session: Session,
+}
+
+impl MessageContext {
+    pub(crate) fn new(msg: Message, session: Session) -> Self {
+        Self { msg, session }
+    }
+
+    pub fn message(&self) -> &Message {
+        &self.msg
+    }
+
+    pub fn into_message(self) -> Message {
+        self.msg
+    }
+
+    /// 当前登录的用户
+    pub fn user(&self) -> &Contact {
+        self.session.user()
+    }
+
+    /// 消息所在的会话: 群消息为群, 自己在手机上发出的消息为接收方, 否则为发送方
+    pub fn chat_user_name(&self) -> &str {
+        if self.msg.from_user_name == self.session.user().user_name {
+            &self.msg.to_user_name
+        } else {
+            &self.msg.from_user_name
+        }
+    }
+
+    /// 在消息所在的会话中回复文本
+    pub async fn reply_text(&self, content: &str) -> Result<SentMessage, Error> {
+        self.session.send_text(self.chat_user_name(), content).await
+    }
+
+    /// 在消息所在的会话中回复图片
+    pub async fn reply_image(&self, file: &MediaFile) -> Result<SentMessage, Error> {
+        self.session.send_image(self.chat_user_name(), file).await
+    }
+
+    /// 在消息所在的会话中回复文件
+    pub async fn reply_file(&self, file: &MediaFile) -> Result<SentMessage, Error> {
+        self.session.send_file(self.chat_user_name(), file).await
+    }
+
+    /// 把当前消息转发给其他用户或者群
+    pub async fn forward_to(&self, to_user_name: &str) -> Result<SentMessage, Error> {
+        let mut sent = self
+            .session
+            .forward(&self.msg, &[to_user_name], Duration::ZERO)
+            .await
+            .map_err(|e| match e {
+                Error::Forward { source, .. } => *source,
+                e => e,
+            })?;
+        Ok(sent.remove(0))
+    }
+
+    /// 撤回自己发送的消息, 例如[`MessageContext::reply_text`]的返回值
+    pub async fn revoke(&self, msg: &SentMessage) -> Result<(), Error> {
+        self.session.revoke(msg).await
+    }
+
+    /// 从联系人缓存中查找发送者, 群消息的发送者不是好友时使用群成员信息
+    pub fn sender(&self) -> Option<Contact> {
+        let user_name = self.msg.sender_user_name();
+        if let Some(contact) = self.session.contact_store().get(user_name) {
+            return Some(contact);
+        }
+
+        let group = self.group()?;
+        let member = group.member(user_name)?;
+        Some(Contact {
+            uin: member.uin,
+            user_name: member.user_name.clone(),
+            nick_name: member.nick_name.clone(),
+            display_name: member.display_name.clone(),
+            attr_status: member.attr_status,
+            py_initial: member.py_initial.clone(),
+            py_quan_pin: member.py_quan_pin.clone(),
+            remark_pyinitial: member.remark_pyinitial.clone(),
+            remark_pyquan_pin: member.remark_pyquan_pin.clone(),
+            key_word: member.key_word.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// 从联系人缓存中查找消息所在的群, 不是群消息时返回None
+    pub fn group(&self) -> Option<Group> {
+        if !self.msg.is_from_group() {
+            return None;
+        }
+        self.session
+            .contact_store()
+            .get(self.chat_user_name())
+            .filter(Contact::is_group)
+            .map(Group::new)
+    }
+}
+
+impl Deref for MessageContext {
+    type Target = Message;
+
+    fn deref(&self) -> &Self::Target {
+        &self.msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contact::Member;
+
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let session = Session::test();
+        session.contact_store().load(vec![Contact {
+            user_name: "@@group".to_string(),
+            member_list: vec![Member {
+                user_name: "@member".to_string(),
+                display_name: "群昵称".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }]);
+
+        let msg = Message {
+            from_user_name: "@@group".to_string(),
+            to_user_name: "@self".to_string(),
+            content: "@member:<br/>hello".to_string(),
+            ..Default::default()
+        };
+        let ctx = MessageContext::new(msg, session.clone());
+        assert_eq!(ctx.chat_user_name(), "@@group");
+        assert_eq!(ctx.body(), "hello");
+        assert_eq!(ctx.group().unwrap().user_name, "@@group");
+        assert_eq!(ctx.sender().unwrap().display_name, "群昵称");
+
+        // 自己在手机上发出的消息回复给接收方
+        let msg = Message {
+            from_user_name: "@self".to_string(),
+            to_user_name: "@friend".to_string(),
+            content: "hi".to_string(),
+            ..Default::default()
+        };
+        let ctx = MessageContext::new(msg, session);
+        assert_eq!(ctx.chat_user_name(), "@friend");
+        assert!(ctx.group().is_none());
+        assert!(ctx.sender().is_none());
+    }
+}
@@ -0,0 +1,168 @@
+use std::{future::Future, sync::Arc};
+
+use crate::handler::{handler_fn, BoxFuture, Ctx, Handler};
+
+use super::{Matcher, MessageContext};
+
+/// 中间件的返回值, 决定是否继续执行后续的中间件和handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    Abort,
+}
+
+struct Route<S> {
+    matcher: Matcher,
+    handler: Handler<S, MessageContext>,
+}
+
+/// 消息分发器, 按注册顺序执行before中间件、所有匹配的handler和after中间件
+///
+/// 任意中间件返回[`Flow::Abort`]时中止本条消息的处理
+pub struct MessageDispatcher<S = ()> {
+    before: Vec<Handler<S, MessageContext, Flow>>,
+    routes: Vec<Route<S>>,
+    after: Vec<Handler<S, MessageContext, Flow>>,
+}
+
+impl<S> Default for MessageDispatcher<S> {
+    fn default() -> Self {
+        Self {
+            before: Vec::new(),
+            routes: Vec::new(),
+            after: Vec::new(),
+        }
+    }
+}
+
+impl<S> MessageDispatcher<S>
+where
+    S: Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册handler, 消息满足matcher时执行
+    pub fn on<F, Fut>(mut self, matcher: Matcher, handler: F) -> Self
+    where
+        F: Fn(Ctx<S, MessageContext>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.routes.push(Route {
+            matcher,
+            handler: handler_fn(handler),
+        });
+        self
+    }
+
+    /// 添加在handler之前执行的中间件
+    pub fn before<F, Fut>(mut self, middleware: F) -> Self
+    where
+        F: Fn(Ctx<S, MessageContext>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Flow> + Send + 'static,
+    {
+        self.before.push(handler_fn(middleware));
+        self
+    }
+
+    /// 添加在handler之后执行的中间件
+    pub fn after<F, Fut>(mut self, middleware: F) -> Self
+    where
+        F: Fn(Ctx<S, MessageContext>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Flow> + Send + 'static,
+    {
+        self.after.push(handler_fn(middleware));
+        self
+    }
+
+    /// 分发一条消息
+    pub async fn dispatch(&self, ctx: Ctx<S, MessageContext>) {
+        for middleware in self.before.iter() {
+            if middleware(ctx.clone()).await == Flow::Abort {
+                return;
+            }
+        }
+
+        for route in self.routes.iter() {
+            if route.matcher.matches(&ctx) {
+                (route.handler)(ctx.clone()).await;
+            }
+        }
+
+        for middleware in self.after.iter() {
+            if middleware(ctx.clone()).await == Flow::Abort {
+                return;
+            }
+        }
+    }
+
+    /// 转换为消息handler, 通过[`Bot::set_message_handler`](crate::bot::Bot::set_message_handler)安装
+    pub fn into_handler(
+        self,
+    ) -> impl Fn(Ctx<S, MessageContext>) -> BoxFuture<'static, ()> + Send + Sync + 'static {
+        let dispatcher = Arc::new(self);
+        move |ctx| {
+            let dispatcher = dispatcher.clone();
+            Box::pin(async move { dispatcher.dispatch(ctx).await })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::{
+        bot::Session,
+        message::{matcher, Message, MsgType},
+    };
+
+    use super::*;
+
+    type Log = Mutex<Vec<&'static str>>;
+
+    fn ctx(state: &Arc<Log>, content: &str) -> Ctx<Log, MessageContext> {
+        let msg = Message {
+            from_user_name: "@friend".to_string(),
+            msg_type: MsgType::Text,
+            content: content.to_string(),
+            ..Default::default()
+        };
+        Ctx::new(state.clone(), MessageContext::new(msg, Session::test()))
+    }
+
+    #[tokio::test]
+    async fn test_dispatch() {
+        let dispatcher = MessageDispatcher::<Log>::new()
+            .before(|ctx| async move {
+                ctx.state().lock().unwrap().push("before");
+                if ctx.content == "stop" {
+                    Flow::Abort
+                } else {
+                    Flow::Continue
+                }
+            })
+            .on(matcher::keyword("ping"), |ctx| async move {
+                ctx.state().lock().unwrap().push("ping");
+            })
+            .on(matcher::from_group(), |ctx| async move {
+                ctx.state().lock().unwrap().push("group");
+            })
+            .on(matcher::is_text(), |ctx| async move {
+                ctx.state().lock().unwrap().push("text");
+            })
+            .after(|ctx| async move {
+                ctx.state().lock().unwrap().push("after");
+                Flow::Continue
+            });
+
+        let state = Arc::new(Log::default());
+        dispatcher.dispatch(ctx(&state, "ping")).await;
+        dispatcher.dispatch(ctx(&state, "stop")).await;
+        assert_eq!(
+            *state.lock().unwrap(),
+            vec!["before", "ping", "text", "after", "before"]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{bot::Bot, errors::Error};
+
+use super::Message;
+
+/// 好友请求, 来自MsgType为37的认证消息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FriendRequest {
+    /// 请求者的UserName
+    pub user_name: String,
+    pub nick_name: String,
+    /// 验证消息
+    pub content: String,
+    /// 通过好友请求时需要的凭证
+    pub ticket: String,
+    /// 添加来源, 例如搜索微信号、群聊、名片分享
+    pub scene: i32,
+}
+
+impl FriendRequest {
+    /// 从认证消息的RecommendInfo中解析好友请求, 不是好友请求时返回None
+    pub fn parse(msg: &Message) -> Option<Self> {
+        if !msg.is_friend_request() {
+            return None;
+        }
+
+        let info = &msg.recommend_info;
+        if info.user_name.is_empty() || info.ticket.is_empty() {
+            return None;
+        }
+        Some(Self {
+            user_name: info.user_name.clone(),
+            nick_name: info.nick_name.clone(),
+            content: info.content.clone(),
+            ticket: info.ticket.clone(),
+            scene: info.scene,
+        })
+    }
+
+    /// 通过好友请求, 同[`Bot::accept_friend`]
+    pub async fn accept<S>(&self, bot: &Bot<S>) -> Result<(), Error>
+    where
+        S: Send + Sync + 'static,
+    {
+        bot.accept_friend(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::{MsgType, RecommendInfo};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_friend_request() {
+        let msg = Message {
+            from_user_name: "fmessage".to_string(),
+            msg_type: MsgType::Verify,
+            recommend_info: RecommendInfo {
+                user_name: "@stranger".to_string(),
+                nick_name: "张三".to_string(),
+                content: "我是张三".to_string(),
+                ticket: "v4_abc@stranger".to_string(),
+                scene: 30,
+                op_code: 2,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let request = FriendRequest::parse(&msg).unwrap();
+        assert_eq!(request.user_name, "@stranger");
+        assert_eq!(request.nick_name, "张三");
+        assert_eq!(request.content, "我是张三");
+        assert_eq!(request.ticket, "v4_abc@stranger");
+        assert_eq!(request.scene, 30);
+
+        let text = Message {
+            msg_type: MsgType::Text,
+            content: "hello".to_string(),
+            ..Default::default()
+        };
+        assert!(FriendRequest::parse(&text).is_none());
+    }
+}
@@ -1,3 +1,15 @@
-use crate::errors::Error;
+use log::warn;
 
-pub type MessageErrorHandler = fn(err: Error);
+use crate::{
+    errors::Error,
+    handler::{Ctx, Handler},
+};
+
+/// 获取消息发生错误的handle, 返回Ok则尝试继续监听
+pub type MessageErrorHandler<S> = Handler<S, Error, Result<(), Error>>;
+
+/// 默认忽略错误并继续监听
+pub async fn default_message_error_handler<S>(ctx: Ctx<S, Error>) -> Result<(), Error> {
+    warn!("message loop error: {}", ctx.data());
+    Ok(())
+}
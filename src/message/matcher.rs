@@ -0,0 +1,191 @@
+use std::{collections::HashSet, sync::Arc};
+
+pub use regex::Regex;
+
+use crate::{consts::SPECIAL_USER_NAMES, contact::ContactStore};
+
+use super::{Message, MsgType};
+
+/// 消息匹配器, 可以通过[`Matcher::and`]、[`Matcher::or`]和`!`组合
+#[derive(Clone)]
+pub struct Matcher(Arc<dyn Fn(&Message) -> bool + Send + Sync>);
+
+impl Matcher {
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&Message) -> bool + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    pub fn matches(&self, msg: &Message) -> bool {
+        (self.0)(msg)
+    }
+
+    /// 两个条件同时满足
+    pub fn and(self, other: Matcher) -> Self {
+        Self::new(move |msg| self.matches(msg) && other.matches(msg))
+    }
+
+    /// 满足任意一个条件
+    pub fn or(self, other: Matcher) -> Self {
+        Self::new(move |msg| self.matches(msg) || other.matches(msg))
+    }
+}
+
+/// 条件取反
+impl std::ops::Not for Matcher {
+    type Output = Matcher;
+
+    fn not(self) -> Self::Output {
+        Self::new(move |msg| !self.matches(msg))
+    }
+}
+
+impl std::fmt::Debug for Matcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Matcher")
+    }
+}
+
+/// 匹配所有消息
+pub fn any() -> Matcher {
+    Matcher::new(|_| true)
+}
+
+pub fn msg_type(msg_type: MsgType) -> Matcher {
+    Matcher::new(move |msg| msg.msg_type == msg_type)
+}
+
+pub fn is_text() -> Matcher {
+    Matcher::new(Message::is_text)
+}
+
+pub fn is_image() -> Matcher {
+    Matcher::new(Message::is_image)
+}
+
+/// 撤回消息, 可以通过[`Message::revoked`]获取被撤回的消息
+pub fn is_revoke() -> Matcher {
+    Matcher::new(Message::is_revoke)
+}
+
+/// 好友请求, 可以通过[`Message::friend_request`]获取请求详情
+pub fn is_friend_request() -> Matcher {
+    Matcher::new(Message::is_friend_request)
+}
+
+/// 群消息
+pub fn from_group() -> Matcher {
+    Matcher::new(Message::is_from_group)
+}
+
+/// 私聊消息, 排除群消息和文件传输助手等特殊账号
+///
+/// 仅根据消息本身无法区分好友、公众号、陌生人和自己, 只需要好友消息时使用[`from_friend`]
+pub fn from_private() -> Matcher {
+    Matcher::new(|msg| {
+        !msg.is_from_group()
+            && msg.from_user_name.starts_with('@')
+            && !SPECIAL_USER_NAMES.contains(&msg.from_user_name.as_str())
+    })
+}
+
+/// 好友发送的私聊消息, 根据联系人缓存中的[`Contact::is_friend`](crate::contact::Contact::is_friend)判断
+pub fn from_friend(store: ContactStore) -> Matcher {
+    Matcher::new(move |msg| {
+        !msg.is_from_group()
+            && store
+                .get(&msg.from_user_name)
+                .is_some_and(|contact| contact.is_friend())
+    })
+}
+
+/// 群消息中@了自己
+pub fn is_at_me() -> Matcher {
+    Matcher::new(|msg| msg.is_from_group() && msg.is_at)
+}
+
+/// 消息内容包含关键字, 群消息不包含发送者前缀
+pub fn keyword(keyword: impl Into<String>) -> Matcher {
+    let keyword = keyword.into();
+    Matcher::new(move |msg| msg.body().contains(&keyword))
+}
+
+/// 消息内容匹配正则表达式, 群消息不包含发送者前缀
+pub fn regex(re: Regex) -> Matcher {
+    Matcher::new(move |msg| re.is_match(msg.body()))
+}
+
+/// 发送者在给定的UserName中, 群消息使用群成员的UserName判断
+pub fn sender_in<I, T>(user_names: I) -> Matcher
+where
+    I: IntoIterator<Item = T>,
+    T: Into<String>,
+{
+    let user_names = user_names
+        .into_iter()
+        .map(Into::into)
+        .collect::<HashSet<String>>();
+    Matcher::new(move |msg| user_names.contains(msg.sender_user_name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contact::Contact;
+
+    fn text(from: &str, content: &str) -> Message {
+        Message {
+            from_user_name: from.to_string(),
+            to_user_name: "@self".to_string(),
+            msg_type: MsgType::Text,
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_matchers() {
+        let friend_msg = text("@friend", "hello world");
+        let group_msg = text("@@group", "@member:<br/>hello");
+
+        assert!(from_private().matches(&friend_msg));
+        assert!(!from_private().matches(&group_msg));
+        assert!(!from_private().matches(&text("filehelper", "hi")));
+        assert!(from_group().and(is_text()).matches(&group_msg));
+        assert!(!(!from_group()).matches(&group_msg));
+        assert!(is_image().or(keyword("world")).matches(&friend_msg));
+        assert!(!keyword("member").matches(&group_msg));
+        assert!(regex(Regex::new(r"^hello\s").unwrap()).matches(&friend_msg));
+        assert!(regex(Regex::new(r"^hello$").unwrap()).matches(&group_msg));
+        assert!(!is_at_me().matches(&group_msg));
+
+        let senders = sender_in(["@member"]);
+        assert!(senders.matches(&group_msg));
+        assert!(!senders.matches(&friend_msg));
+    }
+
+    #[test]
+    fn test_from_friend() {
+        let store = ContactStore::default();
+        store.load(vec![
+            Contact {
+                user_name: "@friend".to_string(),
+                contact_flag: 3,
+                ..Default::default()
+            },
+            Contact {
+                user_name: "@mp".to_string(),
+                contact_flag: 3,
+                verify_flag: 8,
+                ..Default::default()
+            },
+        ]);
+
+        let from_friend = from_friend(store);
+        assert!(from_friend.matches(&text("@friend", "hi")));
+        assert!(!from_friend.matches(&text("@mp", "hi")));
+        assert!(!from_friend.matches(&text("@stranger", "hi")));
+    }
+}
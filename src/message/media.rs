@@ -0,0 +1,244 @@
+use std::path::Path;
+
+use bytes::Bytes;
+use reqwest::{header::CONTENT_TYPE, Response};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+
+use crate::errors::Error;
+
+/// 上传媒体文件后得到的媒体ID
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaId(String);
+
+impl std::fmt::Display for MediaId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<String> for MediaId {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl MediaId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// 待上传的媒体文件
+#[derive(Debug, Clone)]
+pub struct MediaFile {
+    name: String,
+    data: Vec<u8>,
+    md5: String,
+}
+
+impl MediaFile {
+    pub fn new(name: impl Into<String>, data: Vec<u8>) -> Self {
+        let md5 = format!("{:x}", md5::compute(&data));
+        Self {
+            name: name.into(),
+            data,
+            md5,
+        }
+    }
+
+    /// 从本地文件读取, 文件名取路径的最后一段
+    pub async fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or(Error::OpenFile(format!(
+                "无效的文件路径: {}",
+                path.display()
+            )))?;
+        let data = tokio::fs::read(path)
+            .await
+            .map_err(|e| Error::OpenFile(format!("读取文件{}失败: {e}", path.display())))?;
+        Ok(Self::new(name, data))
+    }
+
+    /// 从AsyncRead中读取全部内容
+    pub async fn from_reader<R>(name: impl Into<String>, mut reader: R) -> Result<Self, Error>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut data = Vec::new();
+        reader
+            .read_to_end(&mut data)
+            .await
+            .map_err(|e| Error::OpenFile(format!("读取数据失败: {e}")))?;
+        Ok(Self::new(name, data))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// 文件内容的md5, 十六进制小写
+    pub fn md5(&self) -> &str {
+        &self.md5
+    }
+
+    /// 根据文件名推断MIME类型, 推断不出时根据文件头判断
+    pub fn content_type(&self) -> String {
+        match mime_guess::from_path(&self.name).first() {
+            Some(mime) => mime.to_string(),
+            None => sniff_content_type(&self.data).to_string(),
+        }
+    }
+
+    /// 上传接口的mediatype参数: pic、video或者doc
+    pub(crate) fn media_type(&self) -> &'static str {
+        let content_type = self.content_type();
+        if content_type.starts_with("image/") {
+            "pic"
+        } else if content_type.starts_with("video/") {
+            "video"
+        } else {
+            "doc"
+        }
+    }
+}
+
+/// 下载视频时请求的字节范围
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MediaRange {
+    pub start: u64,
+    /// 结束位置(包含), None表示直到文件末尾
+    pub end: Option<u64>,
+}
+
+impl std::fmt::Display for MediaRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.end {
+            Some(end) => write!(f, "bytes={}-{}", self.start, end),
+            None => write!(f, "bytes={}-", self.start),
+        }
+    }
+}
+
+/// 下载媒体文件的响应体, 可以逐块读取避免大文件占用过多内存
+#[derive(Debug)]
+pub struct MediaBody {
+    resp: Response,
+}
+
+impl MediaBody {
+    pub(crate) fn new(resp: Response) -> Self {
+        Self { resp }
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+    }
+
+    pub fn content_length(&self) -> Option<u64> {
+        self.resp.content_length()
+    }
+
+    /// 读取下一块数据, 读取完毕返回None
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, Error> {
+        Ok(self.resp.chunk().await?)
+    }
+
+    /// 读取全部数据
+    pub async fn bytes(self) -> Result<Bytes, Error> {
+        Ok(self.resp.bytes().await?)
+    }
+
+    /// 逐块写入到本地文件
+    pub async fn save(mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let mut file = tokio::fs::File::create(path)
+            .await
+            .map_err(|e| Error::OpenFile(format!("创建文件{}失败: {e}", path.display())))?;
+        while let Some(chunk) = self.chunk().await? {
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| Error::OpenFile(format!("写入文件{}失败: {e}", path.display())))?;
+        }
+        file.flush()
+            .await
+            .map_err(|e| Error::OpenFile(format!("写入文件{}失败: {e}", path.display())))?;
+        Ok(())
+    }
+}
+
+/// 根据常见文件格式的魔数判断MIME类型
+fn sniff_content_type(data: &[u8]) -> &'static str {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+        "image/jpeg"
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        "image/gif"
+    } else if data.starts_with(b"BM") {
+        "image/bmp"
+    } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        "image/webp"
+    } else if data.len() >= 8 && &data[4..8] == b"ftyp" {
+        "video/mp4"
+    } else if data.starts_with(b"%PDF") {
+        "application/pdf"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_type() {
+        let file = MediaFile::new("a.png", vec![]);
+        assert_eq!(file.content_type(), "image/png");
+        assert_eq!(file.media_type(), "pic");
+
+        let file = MediaFile::new("video", b"\x00\x00\x00\x18ftypmp42".to_vec());
+        assert_eq!(file.content_type(), "video/mp4");
+        assert_eq!(file.media_type(), "video");
+
+        let file = MediaFile::new("unknown", b"hello".to_vec());
+        assert_eq!(file.content_type(), "application/octet-stream");
+        assert_eq!(file.media_type(), "doc");
+        assert_eq!(file.md5(), "5d41402abc4b2a76b9719d911017c592");
+    }
+
+    #[test]
+    fn test_media_range() {
+        let range = MediaRange::default();
+        assert_eq!(range.to_string(), "bytes=0-");
+        let range = MediaRange {
+            start: 100,
+            end: Some(199),
+        };
+        assert_eq!(range.to_string(), "bytes=100-199");
+    }
+
+    #[tokio::test]
+    async fn test_from_reader() {
+        let file = MediaFile::from_reader("a.gif", &b"GIF89a"[..])
+            .await
+            .unwrap();
+        assert_eq!(file.size(), 6);
+        assert_eq!(file.content_type(), "image/gif");
+    }
+}
@@ -1,12 +1,543 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    bot::Bot,
+    contact::Contact,
+    errors::Error,
+    handler::{Ctx, Handler},
+};
+
+mod context;
+mod dispatcher;
+mod friend_request;
 mod handle;
+pub mod matcher;
+mod media;
+mod revoke;
+mod sent;
+
+pub use context::MessageContext;
+pub use dispatcher::{Flow, MessageDispatcher};
+pub use friend_request::FriendRequest;
+pub use handle::{default_message_error_handler, MessageErrorHandler};
+pub use matcher::Matcher;
+pub use media::{MediaBody, MediaFile, MediaId, MediaRange};
+pub use revoke::Revoked;
+pub(crate) use sent::SendMessage;
+pub use sent::SentMessage;
+
+/// 消息类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "i32", into = "i32")]
+pub enum MsgType {
+    /// 文本消息
+    Text,
+    /// 图片消息
+    Image,
+    /// 语音消息
+    Voice,
+    /// 认证消息(好友请求)
+    Verify,
+    /// 好友推荐消息
+    PossibleFriend,
+    /// 名片消息
+    Card,
+    /// 视频消息
+    Video,
+    /// 表情消息
+    Emoticon,
+    /// 地理位置消息
+    Location,
+    /// 分享链接、文件等APP消息
+    App,
+    /// VOIP消息
+    Voip,
+    /// 状态通知消息
+    StatusNotify,
+    /// VOIP结束消息
+    VoipNotify,
+    /// VOIP邀请
+    VoipInvite,
+    /// 小视频消息
+    MicroVideo,
+    /// 系统公告
+    SysNotice,
+    /// 系统消息
+    System,
+    /// 撤回消息
+    Revoke,
+    /// 未知的消息类型
+    Unknown(i32),
+}
+
+impl From<i32> for MsgType {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => MsgType::Text,
+            3 => MsgType::Image,
+            34 => MsgType::Voice,
+            37 => MsgType::Verify,
+            40 => MsgType::PossibleFriend,
+            42 => MsgType::Card,
+            43 => MsgType::Video,
+            47 => MsgType::Emoticon,
+            48 => MsgType::Location,
+            49 => MsgType::App,
+            50 => MsgType::Voip,
+            51 => MsgType::StatusNotify,
+            52 => MsgType::VoipNotify,
+            53 => MsgType::VoipInvite,
+            62 => MsgType::MicroVideo,
+            9999 => MsgType::SysNotice,
+            10000 => MsgType::System,
+            10002 => MsgType::Revoke,
+            v => MsgType::Unknown(v),
+        }
+    }
+}
+
+impl From<MsgType> for i32 {
+    fn from(value: MsgType) -> Self {
+        match value {
+            MsgType::Text => 1,
+            MsgType::Image => 3,
+            MsgType::Voice => 34,
+            MsgType::Verify => 37,
+            MsgType::PossibleFriend => 40,
+            MsgType::Card => 42,
+            MsgType::Video => 43,
+            MsgType::Emoticon => 47,
+            MsgType::Location => 48,
+            MsgType::App => 49,
+            MsgType::Voip => 50,
+            MsgType::StatusNotify => 51,
+            MsgType::VoipNotify => 52,
+            MsgType::VoipInvite => 53,
+            MsgType::MicroVideo => 62,
+            MsgType::SysNotice => 9999,
+            MsgType::System => 10000,
+            MsgType::Revoke => 10002,
+            MsgType::Unknown(v) => v,
+        }
+    }
+}
+
+/// 缺少MsgType字段的消息按未知类型处理
+impl Default for MsgType {
+    fn default() -> Self {
+        Self::Unknown(0)
+    }
+}
+
+/// APP消息的子类型
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "i32", into = "i32")]
+pub enum AppMsgType {
+    /// 非APP消息
+    #[default]
+    None,
+    Text,
+    Image,
+    Audio,
+    Video,
+    /// 分享链接
+    Url,
+    /// 文件附件
+    Attach,
+    Open,
+    Emoji,
+    VoiceRemind,
+    ScanGood,
+    Good,
+    Emotion,
+    CardTicket,
+    /// 共享实时位置
+    RealtimeShareLocation,
+    /// 小程序
+    MiniProgram,
+    /// 转账
+    Transfers,
+    /// 红包
+    RedEnvelopes,
+    ReaderType,
+    /// 未知的APP消息类型
+    Unknown(i32),
+}
+
+impl From<i32> for AppMsgType {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => AppMsgType::None,
+            1 => AppMsgType::Text,
+            2 => AppMsgType::Image,
+            3 => AppMsgType::Audio,
+            4 => AppMsgType::Video,
+            5 => AppMsgType::Url,
+            6 => AppMsgType::Attach,
+            7 => AppMsgType::Open,
+            8 => AppMsgType::Emoji,
+            9 => AppMsgType::VoiceRemind,
+            10 => AppMsgType::ScanGood,
+            13 => AppMsgType::Good,
+            15 => AppMsgType::Emotion,
+            16 => AppMsgType::CardTicket,
+            17 => AppMsgType::RealtimeShareLocation,
+            33 => AppMsgType::MiniProgram,
+            2000 => AppMsgType::Transfers,
+            2001 => AppMsgType::RedEnvelopes,
+            100001 => AppMsgType::ReaderType,
+            v => AppMsgType::Unknown(v),
+        }
+    }
+}
 
-#[derive(Debug, Serialize, Deserialize)]
+impl From<AppMsgType> for i32 {
+    fn from(value: AppMsgType) -> Self {
+        match value {
+            AppMsgType::None => 0,
+            AppMsgType::Text => 1,
+            AppMsgType::Image => 2,
+            AppMsgType::Audio => 3,
+            AppMsgType::Video => 4,
+            AppMsgType::Url => 5,
+            AppMsgType::Attach => 6,
+            AppMsgType::Open => 7,
+            AppMsgType::Emoji => 8,
+            AppMsgType::VoiceRemind => 9,
+            AppMsgType::ScanGood => 10,
+            AppMsgType::Good => 13,
+            AppMsgType::Emotion => 15,
+            AppMsgType::CardTicket => 16,
+            AppMsgType::RealtimeShareLocation => 17,
+            AppMsgType::MiniProgram => 33,
+            AppMsgType::Transfers => 2000,
+            AppMsgType::RedEnvelopes => 2001,
+            AppMsgType::ReaderType => 100001,
+            AppMsgType::Unknown(v) => v,
+        }
+    }
+}
+
+/// 好友请求、名片消息中携带的推荐信息
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecommendInfo {
+    #[serde(rename = "UserName")]
+    pub user_name: String,
+    #[serde(rename = "NickName")]
+    pub nick_name: String,
+    #[serde(rename = "QQNum")]
+    pub qq_num: i64,
+    #[serde(rename = "Province")]
+    pub province: String,
+    #[serde(rename = "City")]
+    pub city: String,
+    #[serde(rename = "Content")]
+    pub content: String,
+    #[serde(rename = "Signature")]
+    pub signature: String,
+    #[serde(rename = "Alias")]
+    pub alias: String,
+    #[serde(rename = "Scene")]
+    pub scene: i32,
+    #[serde(rename = "VerifyFlag")]
+    pub verify_flag: i32,
+    #[serde(rename = "AttrStatus")]
+    pub attr_status: i64,
+    #[serde(rename = "Sex")]
+    pub sex: i32,
+    #[serde(rename = "Ticket")]
+    pub ticket: String,
+    #[serde(rename = "OpCode")]
+    pub op_code: i32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppInfo {
+    #[serde(rename = "AppID")]
+    pub app_id: String,
+    #[serde(rename = "Type")]
+    pub r#type: i32,
+}
+
+/// 同步接口返回的消息
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Message {
+    #[serde(rename = "MsgId")]
+    pub msg_id: String,
+    #[serde(rename = "NewMsgId")]
+    pub new_msg_id: i64,
+    #[serde(rename = "FromUserName")]
+    pub from_user_name: String,
+    #[serde(rename = "ToUserName")]
+    pub to_user_name: String,
+    #[serde(rename = "MsgType")]
+    pub msg_type: MsgType,
+    #[serde(rename = "SubMsgType")]
+    pub sub_msg_type: i32,
+    #[serde(rename = "Content")]
+    pub content: String,
+    #[serde(rename = "OriContent")]
+    pub ori_content: String,
+    #[serde(rename = "Status")]
+    pub status: i32,
+    #[serde(rename = "ImgStatus")]
+    pub img_status: i32,
+    #[serde(rename = "CreateTime")]
+    pub create_time: i64,
+    #[serde(rename = "VoiceLength")]
+    pub voice_length: i64,
+    #[serde(rename = "PlayLength")]
+    pub play_length: i64,
+    #[serde(rename = "MediaId")]
+    pub media_id: String,
+    #[serde(rename = "FileName")]
+    pub file_name: String,
+    #[serde(rename = "FileSize")]
+    pub file_size: String,
+    #[serde(rename = "EncryFileName")]
+    pub encry_file_name: String,
+    #[serde(rename = "Url")]
+    pub url: String,
+    #[serde(rename = "AppMsgType")]
+    pub app_msg_type: AppMsgType,
+    #[serde(rename = "AppInfo")]
+    pub app_info: AppInfo,
+    #[serde(rename = "StatusNotifyCode")]
+    pub status_notify_code: i32,
+    #[serde(rename = "StatusNotifyUserName")]
+    pub status_notify_user_name: String,
+    #[serde(rename = "RecommendInfo")]
+    pub recommend_info: RecommendInfo,
+    #[serde(rename = "ForwardFlag")]
+    pub forward_flag: i32,
+    #[serde(rename = "HasProductId")]
+    pub has_product_id: i32,
+    #[serde(rename = "Ticket")]
+    pub ticket: String,
+    #[serde(rename = "ImgHeight")]
+    pub img_height: i32,
+    #[serde(rename = "ImgWidth")]
+    pub img_width: i32,
+    /// 是否@了自己
+    #[serde(skip)]
     pub is_at: bool,
+    /// 未建模的字段原样保留
+    #[serde(flatten)]
+    pub raw: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Message {
+    /// 是否为群消息
+    pub fn is_from_group(&self) -> bool {
+        self.from_user_name.starts_with("@@") || self.to_user_name.starts_with("@@")
+    }
+
+    /// 实际发送者的UserName, 群消息的发送者在Content开头, 格式为`@xxx:<br/>内容`
+    pub fn sender_user_name(&self) -> &str {
+        self.split_group_content()
+            .map(|(sender, _)| sender)
+            .unwrap_or(&self.from_user_name)
+    }
+
+    /// 去掉群消息发送者前缀后的消息内容
+    pub fn body(&self) -> &str {
+        self.split_group_content()
+            .map(|(_, body)| body)
+            .unwrap_or(&self.content)
+    }
+
+    /// 根据自己的群昵称和微信昵称判断群消息是否@了自己, 结果保存在[`Message::is_at`]
+    pub(crate) fn detect_at(&mut self, user: &Contact, group: Option<&Contact>) {
+        if !self.from_user_name.starts_with("@@") || !self.is_text() {
+            return;
+        }
+
+        let display_name = group
+            .and_then(|g| g.member_list.iter().find(|m| m.user_name == user.user_name))
+            .map(|m| m.display_name.as_str());
+        let body = self.body();
+        self.is_at = display_name
+            .into_iter()
+            .chain(Some(user.nick_name.as_str()))
+            .filter(|name| !name.is_empty())
+            .any(|name| contains_at(body, name));
+    }
+
+    fn split_group_content(&self) -> Option<(&str, &str)> {
+        if !self.from_user_name.starts_with("@@") {
+            return None;
+        }
+        self.content
+            .split_once(":<br/>")
+            .filter(|(sender, _)| sender.starts_with('@'))
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.msg_type == MsgType::Text && self.sub_msg_type != 48
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.msg_type == MsgType::Image
+    }
+
+    pub fn is_voice(&self) -> bool {
+        self.msg_type == MsgType::Voice
+    }
+
+    pub fn is_video(&self) -> bool {
+        self.msg_type == MsgType::Video || self.msg_type == MsgType::MicroVideo
+    }
+
+    pub fn is_emoticon(&self) -> bool {
+        self.msg_type == MsgType::Emoticon
+    }
+
+    pub fn is_card(&self) -> bool {
+        self.msg_type == MsgType::Card
+    }
+
+    /// 位置消息在网页版中为子类型为48的文本消息
+    pub fn is_location(&self) -> bool {
+        self.msg_type == MsgType::Location
+            || (self.msg_type == MsgType::Text && self.sub_msg_type == 48)
+    }
+
+    pub fn is_app(&self) -> bool {
+        self.msg_type == MsgType::App
+    }
+
+    /// 是否为文件附件消息
+    pub fn is_attachment(&self) -> bool {
+        self.is_app() && self.app_msg_type == AppMsgType::Attach
+    }
+
+    pub fn is_system(&self) -> bool {
+        self.msg_type == MsgType::System || self.msg_type == MsgType::SysNotice
+    }
+
+    pub fn is_revoke(&self) -> bool {
+        self.msg_type == MsgType::Revoke
+    }
+
+    /// 解析撤回消息, 得到被撤回消息的MsgId和提示内容
+    pub fn revoked(&self) -> Option<Revoked> {
+        Revoked::parse(self)
+    }
+
+    /// 是否为好友请求
+    pub fn is_friend_request(&self) -> bool {
+        self.msg_type == MsgType::Verify
+    }
+
+    /// 解析好友请求, 可以通过[`FriendRequest::accept`]通过请求
+    pub fn friend_request(&self) -> Option<FriendRequest> {
+        FriendRequest::parse(self)
+    }
+
+    /// 是否带有可以下载的媒体文件
+    pub fn has_media(&self) -> bool {
+        self.is_image()
+            || self.is_voice()
+            || self.is_video()
+            || self.is_emoticon()
+            || (self.is_app() && !self.media_id.is_empty())
+    }
+
+    /// 下载消息中的媒体文件, 同[`Bot::download_media`]
+    pub async fn download<S>(&self, bot: &Bot<S>) -> Result<MediaBody, Error>
+    where
+        S: Send + Sync + 'static,
+    {
+        bot.download_media(self).await
+    }
+}
+
+/// @某人后面跟着的分隔符
+pub(crate) const AT_SEPARATOR: char = '\u{2005}';
+
+/// 内容中是否有`@name`, 后面需要跟分隔符、空格或者在结尾
+fn contains_at(body: &str, name: &str) -> bool {
+    let pattern = format!("@{name}");
+    body.match_indices(&pattern).any(|(i, _)| {
+        let rest = &body[i + pattern.len()..];
+        rest.is_empty() || rest.starts_with(AT_SEPARATOR) || rest.starts_with(' ')
+    })
 }
 
-pub type MessageHandler = fn(msg: Message);
+/// 获取消息成功的handle
+pub type MessageHandler<S> = Handler<S, MessageContext>;
+
+pub async fn default_message_handler<S>(_ctx: Ctx<S, MessageContext>) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_message() {
+        let json = r#"{"MsgId":"6781430853562587045","FromUserName":"@abc","ToUserName":"@def","MsgType":1,"Content":"hello","Status":3,"ImgStatus":1,"CreateTime":1736230702,"VoiceLength":0,"PlayLength":0,"FileName":"","FileSize":"","MediaId":"","Url":"","AppMsgType":0,"StatusNotifyCode":0,"StatusNotifyUserName":"","RecommendInfo":{"UserName":"","NickName":"","QQNum":0,"Province":"","City":"","Content":"","Signature":"","Alias":"","Scene":0,"VerifyFlag":0,"AttrStatus":0,"Sex":0,"Ticket":"","OpCode":0},"ForwardFlag":0,"AppInfo":{"AppID":"","Type":0},"HasProductId":0,"Ticket":"","ImgHeight":0,"ImgWidth":0,"SubMsgType":0,"NewMsgId":6781430853562587045,"OriContent":"","EncryFileName":"","NewField":"keep me"}"#;
+        let msg: Message = serde_json::from_str(json).unwrap();
+        assert_eq!(msg.msg_type, MsgType::Text);
+        assert!(msg.is_text());
+        assert_eq!(msg.content, "hello");
+        assert_eq!(msg.app_msg_type, AppMsgType::None);
+        assert_eq!(msg.raw.get("NewField").unwrap(), "keep me");
+    }
 
-pub fn default_message_handler(_msg: Message) {}
+    #[test]
+    fn test_detect_at() {
+        let user = Contact {
+            user_name: "@self".to_string(),
+            nick_name: "小明".to_string(),
+            ..Default::default()
+        };
+        let group = Contact {
+            user_name: "@@group".to_string(),
+            member_list: vec![crate::contact::Member {
+                user_name: "@self".to_string(),
+                display_name: "明哥".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut msg = Message {
+            from_user_name: "@@group".to_string(),
+            msg_type: MsgType::Text,
+            content: "@abc:<br/>@明哥\u{2005}在吗".to_string(),
+            ..Default::default()
+        };
+
+        msg.detect_at(&user, Some(&group));
+        assert!(msg.is_at);
+        assert_eq!(msg.sender_user_name(), "@abc");
+        assert_eq!(msg.body(), "@明哥\u{2005}在吗");
+
+        msg.content = "@abc:<br/>@小明".to_string();
+        msg.detect_at(&user, None);
+        assert!(msg.is_at);
+
+        // 昵称是别人昵称的前缀时不算@自己
+        msg.content = "@abc:<br/>@小明同学 在吗".to_string();
+        msg.detect_at(&user, Some(&group));
+        assert!(!msg.is_at);
+    }
+
+    #[test]
+    fn test_parse_unknown_msg_type() {
+        let json = r#"{"MsgId":"1","MsgType":12345,"AppMsgType":999}"#;
+        let msg: Message = serde_json::from_str(json).unwrap();
+        assert_eq!(msg.msg_type, MsgType::Unknown(12345));
+        assert_eq!(msg.app_msg_type, AppMsgType::Unknown(999));
+
+        let value = serde_json::to_value(&msg).unwrap();
+        assert_eq!(value["MsgType"], 12345);
+
+        // 缺少MsgType时不能当作文本消息
+        let msg: Message = serde_json::from_str(r#"{"MsgId":"2"}"#).unwrap();
+        assert_eq!(msg.msg_type, MsgType::Unknown(0));
+        assert!(!msg.is_text());
+    }
+}
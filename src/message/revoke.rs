@@ -0,0 +1,79 @@
+use serde::Deserialize;
+
+use super::Message;
+
+/// 对方撤回了一条消息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revoked {
+    /// 被撤回消息的MsgId
+    pub msg_id: String,
+    /// 替换显示的提示, 例如"xxx" 撤回了一条消息
+    pub replace_msg: String,
+}
+
+#[derive(Deserialize)]
+struct SysMsg {
+    #[serde(rename = "revokemsg")]
+    revoke_msg: RevokeMsg,
+}
+
+#[derive(Deserialize)]
+struct RevokeMsg {
+    #[serde(rename = "msgid")]
+    msg_id: String,
+    #[serde(rename = "replacemsg", default)]
+    replace_msg: String,
+}
+
+impl Revoked {
+    /// 解析撤回消息的`<sysmsg type="revokemsg">`内容, 不是撤回消息时返回None
+    pub fn parse(msg: &Message) -> Option<Self> {
+        if !msg.is_revoke() {
+            return None;
+        }
+
+        let content = html_unescape(msg.body());
+        let sys_msg: SysMsg = serde_xml_rs::from_str(&content).ok()?;
+        Some(Self {
+            msg_id: sys_msg.revoke_msg.msg_id,
+            replace_msg: sys_msg.revoke_msg.replace_msg,
+        })
+    }
+}
+
+/// 同步接口返回的XML内容经过了HTML转义
+fn html_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::MsgType;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_revoked() {
+        let msg = Message {
+            from_user_name: "@@group".to_string(),
+            msg_type: MsgType::Revoke,
+            content: "@abc:<br/>&lt;sysmsg type=\"revokemsg\"&gt;&lt;revokemsg&gt;&lt;session&gt;123@chatroom&lt;/session&gt;&lt;oldmsgid&gt;1057920614&lt;/oldmsgid&gt;&lt;msgid&gt;6781430853562587045&lt;/msgid&gt;&lt;replacemsg&gt;&lt;![CDATA[\"张三\" 撤回了一条消息]]&gt;&lt;/replacemsg&gt;&lt;/revokemsg&gt;&lt;/sysmsg&gt;".to_string(),
+            ..Default::default()
+        };
+
+        let revoked = Revoked::parse(&msg).unwrap();
+        assert_eq!(revoked.msg_id, "6781430853562587045");
+        assert_eq!(revoked.replace_msg, "\"张三\" 撤回了一条消息");
+
+        let text = Message {
+            msg_type: MsgType::Text,
+            content: "hello".to_string(),
+            ..Default::default()
+        };
+        assert!(Revoked::parse(&text).is_none());
+    }
+}
@@ -0,0 +1,175 @@
+use chrono::Utc;
+use rand::Rng;
+use serde::Serialize;
+
+use super::{AppMsgType, MediaId, MsgType, AT_SEPARATOR};
+
+/// 发送消息接口的消息体
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct SendMessage {
+    /// 接口使用的消息类型, APP消息为6
+    #[serde(rename = "Type")]
+    pub r#type: i32,
+    #[serde(skip)]
+    pub msg_type: MsgType,
+    #[serde(rename = "Content")]
+    pub content: String,
+    #[serde(rename = "FromUserName")]
+    pub from_user_name: String,
+    #[serde(rename = "ToUserName")]
+    pub to_user_name: String,
+    #[serde(rename = "LocalID")]
+    pub local_id: String,
+    #[serde(rename = "ClientMsgId")]
+    pub client_msg_id: String,
+    #[serde(rename = "MediaId", skip_serializing_if = "String::is_empty")]
+    pub media_id: String,
+    #[serde(rename = "EmojiFlag", skip_serializing_if = "Option::is_none")]
+    pub emoji_flag: Option<i32>,
+}
+
+impl SendMessage {
+    pub fn new(msg_type: MsgType, content: &str, from_user_name: &str, to_user_name: &str) -> Self {
+        let local_id = new_local_id();
+        Self {
+            r#type: msg_type.into(),
+            msg_type,
+            content: content.to_string(),
+            from_user_name: from_user_name.to_string(),
+            to_user_name: to_user_name.to_string(),
+            client_msg_id: local_id.clone(),
+            local_id,
+            media_id: String::new(),
+            emoji_flag: None,
+        }
+    }
+
+    pub fn text(content: &str, from_user_name: &str, to_user_name: &str) -> Self {
+        Self::new(MsgType::Text, content, from_user_name, to_user_name)
+    }
+
+    /// 群聊中@成员的文本消息, 每个@后面跟一个\u{2005}分隔
+    pub fn text_at(
+        names: &[&str],
+        content: &str,
+        from_user_name: &str,
+        to_user_name: &str,
+    ) -> Self {
+        let mut text = String::new();
+        for name in names {
+            text.push('@');
+            text.push_str(name);
+            text.push(AT_SEPARATOR);
+        }
+        text.push_str(content);
+        Self::text(&text, from_user_name, to_user_name)
+    }
+
+    pub fn image(media_id: &MediaId, from_user_name: &str, to_user_name: &str) -> Self {
+        let mut msg = Self::new(MsgType::Image, "", from_user_name, to_user_name);
+        msg.media_id = media_id.to_string();
+        msg
+    }
+
+    pub fn video(media_id: &MediaId, from_user_name: &str, to_user_name: &str) -> Self {
+        let mut msg = Self::new(MsgType::Video, "", from_user_name, to_user_name);
+        msg.media_id = media_id.to_string();
+        msg
+    }
+
+    pub fn emoticon(media_id: &MediaId, from_user_name: &str, to_user_name: &str) -> Self {
+        let mut msg = Self::new(MsgType::Emoticon, "", from_user_name, to_user_name);
+        msg.media_id = media_id.to_string();
+        msg.emoji_flag = Some(2);
+        msg
+    }
+
+    /// 文件消息通过APP消息发送, 内容为描述附件的appmsg XML
+    pub fn file(
+        media_id: &MediaId,
+        file_name: &str,
+        file_size: usize,
+        from_user_name: &str,
+        to_user_name: &str,
+    ) -> Self {
+        let file_ext = file_name.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("");
+        let content = format!(
+            "<appmsg appid='wxeb7ec651dd0aefa9' sdkver=''><title>{}</title><des></des><action></action><type>6</type><content></content><url></url><lowurl></lowurl><appattach><totallen>{}</totallen><attachid>{}</attachid><fileext>{}</fileext></appattach><extinfo></extinfo></appmsg>",
+            xml_escape(file_name),
+            file_size,
+            media_id,
+            xml_escape(file_ext),
+        );
+        let mut msg = Self::new(MsgType::App, &content, from_user_name, to_user_name);
+        msg.r#type = AppMsgType::Attach.into();
+        msg
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\'', "&apos;")
+        .replace('"', "&quot;")
+}
+
+/// 生成LocalID, 毫秒时间戳加上4位随机数
+fn new_local_id() -> String {
+    let r: u16 = rand::thread_rng().gen_range(0..10000);
+    format!("{}{:04}", Utc::now().timestamp_millis(), r)
+}
+
+/// 发送成功的消息, 可以用于撤回或者转发
+#[derive(Debug, Clone)]
+pub struct SentMessage {
+    pub msg_id: String,
+    pub local_id: String,
+    pub msg_type: MsgType,
+    pub from_user_name: String,
+    pub to_user_name: String,
+    pub content: String,
+    pub media_id: String,
+}
+
+impl SentMessage {
+    pub(crate) fn new(msg: SendMessage, msg_id: String) -> Self {
+        Self {
+            msg_id,
+            local_id: msg.local_id,
+            msg_type: msg.msg_type,
+            from_user_name: msg.from_user_name,
+            to_user_name: msg.to_user_name,
+            content: msg.content,
+            media_id: msg.media_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_message() {
+        let media_id = MediaId::from("@crypt_abc".to_string());
+        let msg = SendMessage::file(&media_id, "a&b.pdf", 1024, "@from", "@to");
+        assert_eq!(msg.r#type, 6);
+        assert_eq!(msg.msg_type, MsgType::App);
+        assert!(msg.content.contains("<title>a&amp;b.pdf</title>"));
+        assert!(msg.content.contains("<attachid>@crypt_abc</attachid>"));
+        assert!(msg.content.contains("<fileext>pdf</fileext>"));
+
+        let value = serde_json::to_value(&msg).unwrap();
+        assert_eq!(value["Type"], 6);
+        assert!(value.get("MediaId").is_none());
+        assert!(value.get("EmojiFlag").is_none());
+    }
+
+    #[test]
+    fn test_text_at_message() {
+        let msg = SendMessage::text_at(&["张三", "李四"], "开会了", "@from", "@@group");
+        assert_eq!(msg.content, "@张三\u{2005}@李四\u{2005}开会了");
+        assert_eq!(msg.msg_type, MsgType::Text);
+    }
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+use crate::contact::Contact;
+
+use super::BaseResponse;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseBatchGetContact {
+    #[serde(rename = "BaseResponse")]
+    pub base_response: BaseResponse,
+    #[serde(rename = "Count")]
+    pub count: usize,
+    #[serde(rename = "ContactList")]
+    pub contact_list: Vec<Contact>,
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use crate::contact::Member;
+
+use super::BaseResponse;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseCreateChatroom {
+    #[serde(rename = "BaseResponse")]
+    pub base_response: BaseResponse,
+    #[serde(rename = "Topic", default)]
+    pub topic: String,
+    /// 新建群的UserName
+    #[serde(rename = "ChatRoomName", default)]
+    pub chat_room_name: String,
+    #[serde(rename = "MemberCount", default)]
+    pub member_count: i32,
+    #[serde(rename = "MemberList", default)]
+    pub member_list: Vec<Member>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseUpdateChatroom {
+    #[serde(rename = "BaseResponse")]
+    pub base_response: BaseResponse,
+    #[serde(rename = "MemberCount", default)]
+    pub member_count: i32,
+    /// 本次变更涉及的成员
+    #[serde(rename = "MemberList", default)]
+    pub member_list: Vec<Member>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_create_chatroom() {
+        let json = r#"{"BaseResponse":{"Ret":0,"ErrMsg":"Everything is OK"},"Topic":"test","PYInitial":"","QuanPin":"","MemberCount":2,"MemberList":[{"Uin":0,"UserName":"@a","NickName":"","AttrStatus":0,"PYInitial":"","PYQuanPin":"","RemarkPYInitial":"","RemarkPYQuanPin":"","MemberStatus":0,"DisplayName":"","KeyWord":""},{"Uin":0,"UserName":"@b","NickName":"","AttrStatus":0,"PYInitial":"","PYQuanPin":"","RemarkPYInitial":"","RemarkPYQuanPin":"","MemberStatus":0,"DisplayName":"","KeyWord":""}],"ChatRoomName":"@@abc","BlackList":""}"#;
+        let resp: ResponseCreateChatroom = serde_json::from_str(json).unwrap();
+        assert!(resp.base_response.is_ok());
+        assert_eq!(resp.chat_room_name, "@@abc");
+        assert_eq!(resp.member_list.len(), 2);
+    }
+}
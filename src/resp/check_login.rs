@@ -1,7 +1,49 @@
-use crate::consts::Status;
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::consts::{Status, REGEX_USER_AVATAR};
 
 #[derive(Debug)]
 pub struct ResponseCheckLogin {
     pub status: Status,
     pub raw: String,
+    /// 扫码后返回的用户头像图片数据, 只有状态为Scanned时才有
+    pub avatar: Option<Vec<u8>>,
+}
+
+impl ResponseCheckLogin {
+    pub(crate) fn new(status: Status, raw: String) -> Self {
+        let avatar = match status {
+            Status::Scanned => parse_avatar(&raw),
+            _ => None,
+        };
+        Self {
+            status,
+            raw,
+            avatar,
+        }
+    }
+}
+
+/// 解析`window.userAvatar = 'data:img/jpg;base64,...'`中的头像数据
+fn parse_avatar(raw: &str) -> Option<Vec<u8>> {
+    let data = REGEX_USER_AVATAR.captures(raw)?.get(1)?.as_str();
+    STANDARD.decode(data).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_avatar() {
+        let raw = "window.code=201;window.userAvatar = 'data:img/jpg;base64,/9j/4AAQSkZJRg==';";
+        let resp = ResponseCheckLogin::new(Status::Scanned, raw.to_string());
+        assert_eq!(
+            resp.avatar.unwrap(),
+            vec![0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10, 0x4a, 0x46, 0x49, 0x46]
+        );
+
+        let resp = ResponseCheckLogin::new(Status::Wait, "window.code=408;".to_string());
+        assert!(resp.avatar.is_none());
+    }
 }
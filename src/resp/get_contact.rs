@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+use crate::contact::Contact;
+
+use super::BaseResponse;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseGetContact {
+    #[serde(rename = "BaseResponse")]
+    pub base_response: BaseResponse,
+    #[serde(rename = "MemberCount")]
+    pub member_count: usize,
+    #[serde(rename = "MemberList")]
+    pub member_list: Vec<Contact>,
+    /// 分页游标, 为0时表示已经获取完毕
+    #[serde(rename = "Seq", default)]
+    pub seq: i64,
+}
@@ -1,41 +1,93 @@
+pub use batch_get_contact::ResponseBatchGetContact;
+pub use chatroom::{ResponseCreateChatroom, ResponseUpdateChatroom};
 pub use check_login::ResponseCheckLogin;
+pub use get_contact::ResponseGetContact;
 pub use login_info::LoginInfo;
-pub use sync_check::{ResponseSyncCheck, Selector};
+pub use push_login::ResponsePushLogin;
+pub use revoke_msg::ResponseRevokeMsg;
+pub use send_message::ResponseSendMessage;
+pub use sync_check::{ResponseSyncCheck, RetCode, Selector};
 pub use sync_message::ResponseSyncMessage;
+pub use upload_media::{ResponseCheckUpload, ResponseUploadMedia};
+pub use verify_user::ResponseVerifyUser;
 pub use web_init::{ResponseWebInit, SyncKey};
 
 use serde::{Deserialize, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
 
+mod batch_get_contact;
+mod chatroom;
 mod check_login;
+mod get_contact;
 mod login_info;
+mod push_login;
+mod revoke_msg;
+mod send_message;
 mod sync_check;
 mod sync_message;
-mod user;
+mod upload_media;
+mod verify_user;
 mod web_init;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
-#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "i32", into = "i32")]
 pub enum Ret {
-    Ok = 0,
-    /// ticket error
-    Ticket = -14,
-    /// logic error
-    Logic = -2,
-    /// sys error
-    System = -1,
-    /// param error
-    Param = 1,
-    /// failed login warn
-    FailedLoginWarn = 1100,
-    /// failed login check
-    FailedLoginCheck = 1101,
-    /// cookie invalid
-    CookieInvalid = 1102,
-    /// login environmental abnormality
-    LoginEnvAbnormality = 1203,
-    /// operate too often
-    OperateTooOften = 1205,
+    Ok,
+    /// ticket error, -14
+    Ticket,
+    /// logic error, -2
+    Logic,
+    /// sys error, -1
+    System,
+    /// param error, 1
+    Param,
+    /// failed login warn, 1100
+    FailedLoginWarn,
+    /// failed login check, 1101
+    FailedLoginCheck,
+    /// cookie invalid, 1102
+    CookieInvalid,
+    /// login environmental abnormality, 1203
+    LoginEnvAbnormality,
+    /// operate too often, 1205
+    OperateTooOften,
+    /// 未知的返回码
+    Unknown(i32),
+}
+
+impl From<i32> for Ret {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => Self::Ok,
+            -14 => Self::Ticket,
+            -2 => Self::Logic,
+            -1 => Self::System,
+            1 => Self::Param,
+            1100 => Self::FailedLoginWarn,
+            1101 => Self::FailedLoginCheck,
+            1102 => Self::CookieInvalid,
+            1203 => Self::LoginEnvAbnormality,
+            1205 => Self::OperateTooOften,
+            v => Self::Unknown(v),
+        }
+    }
+}
+
+impl From<Ret> for i32 {
+    fn from(value: Ret) -> Self {
+        match value {
+            Ret::Ok => 0,
+            Ret::Ticket => -14,
+            Ret::Logic => -2,
+            Ret::System => -1,
+            Ret::Param => 1,
+            Ret::FailedLoginWarn => 1100,
+            Ret::FailedLoginCheck => 1101,
+            Ret::CookieInvalid => 1102,
+            Ret::LoginEnvAbnormality => 1203,
+            Ret::OperateTooOften => 1205,
+            Ret::Unknown(v) => v,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,5 +114,10 @@ mod tests {
         let resp: BaseResponse = serde_json::from_str(json).unwrap();
         assert_eq!(resp.ret, Ret::Ok);
         assert_eq!(resp.errmsg, "ok");
+
+        let json = r#"{"Ret":-23,"ErrMsg":""}"#;
+        let resp: BaseResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.ret, Ret::Unknown(-23));
+        assert_eq!(serde_json::to_string(&resp).unwrap(), json);
     }
 }
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// webwxpushloginurl的响应, 成功时返回新的登录UUID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponsePushLogin {
+    #[serde(default)]
+    pub ret: String,
+    #[serde(default)]
+    pub msg: String,
+    #[serde(default)]
+    pub uuid: String,
+}
+
+impl ResponsePushLogin {
+    pub fn is_ok(&self) -> bool {
+        self.ret == "0" && !self.uuid.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_push_login() {
+        let resp: ResponsePushLogin =
+            serde_json::from_str(r#"{"ret":"0","msg":"all ok","uuid":"IYnK2hyaGA=="}"#).unwrap();
+        assert!(resp.is_ok());
+        assert_eq!(resp.uuid, "IYnK2hyaGA==");
+
+        let resp: ResponsePushLogin =
+            serde_json::from_str(r#"{"ret":"1","msg":"param error"}"#).unwrap();
+        assert!(!resp.is_ok());
+    }
+}
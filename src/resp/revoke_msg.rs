@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use super::BaseResponse;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseRevokeMsg {
+    #[serde(rename = "BaseResponse")]
+    pub base_response: BaseResponse,
+    #[serde(rename = "Introduction", default)]
+    pub introduction: String,
+    #[serde(rename = "SysWording", default)]
+    pub sys_wording: String,
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use super::BaseResponse;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseSendMessage {
+    #[serde(rename = "BaseResponse")]
+    pub base_response: BaseResponse,
+    #[serde(rename = "MsgID", default)]
+    pub msg_id: String,
+    #[serde(rename = "LocalID", default)]
+    pub local_id: String,
+}
@@ -3,29 +3,93 @@ use strum::EnumString;
 
 use crate::Error;
 
-#[derive(strum::Display, EnumString, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(strum::Display, EnumString, Debug, Clone, PartialEq, Eq)]
 pub enum Selector {
     /// 正常
     #[strum(serialize = "0")]
     Normal,
+    /// 个人资料或者设置变更
+    #[strum(serialize = "1")]
+    ModProfile,
     /// 新消息
     #[strum(serialize = "2")]
     NewMessage,
+    /// 手机端同步的消息
+    #[strum(serialize = "3")]
+    MobileSync,
     /// 联系人信息变更
     #[strum(serialize = "4")]
     ModContact,
+    /// 手机端操作或者收藏变更
+    #[strum(serialize = "5")]
+    MobileOperation,
     /// 添加或删除联系人
     #[strum(serialize = "6")]
     AddOrDelContact,
     /// 进入或退出聊天室
     #[strum(serialize = "7")]
     ModChatroom,
+    /// 未知的selector, 同样需要拉取一次消息
+    #[strum(default, to_string = "{0}")]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// synccheck的retcode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RetCode {
+    /// 正常
+    Success,
+    /// 手机端退出了网页版登录
+    LogoutFromPhone,
+    /// 在其他地方登录了网页版
+    LogoutElsewhere,
+    /// cookie失效, 需要重新登录
+    CookieInvalid,
+    Unknown(String),
+}
+
+impl From<&str> for RetCode {
+    fn from(value: &str) -> Self {
+        match value {
+            "0" => Self::Success,
+            "1100" => Self::LogoutFromPhone,
+            "1101" => Self::LogoutElsewhere,
+            "1102" => Self::CookieInvalid,
+            _ => Self::Unknown(value.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for RetCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Success => write!(f, "0"),
+            Self::LogoutFromPhone => write!(f, "1100"),
+            Self::LogoutElsewhere => write!(f, "1101"),
+            Self::CookieInvalid => write!(f, "1102"),
+            Self::Unknown(code) => write!(f, "{code}"),
+        }
+    }
+}
+
+impl RetCode {
+    /// 是否已经被服务端登出, 此时需要重新登录
+    pub fn is_logout(&self) -> bool {
+        matches!(
+            self,
+            Self::LogoutFromPhone | Self::LogoutElsewhere | Self::CookieInvalid
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseSyncCheck {
-    #[serde(rename = "retcode")]
-    pub ret_code: String,
+    #[serde(
+        rename = "retcode",
+        serialize_with = "ser_ret_code",
+        deserialize_with = "de_ret_code"
+    )]
+    pub ret_code: RetCode,
     #[serde(
         rename = "selector",
         serialize_with = "ser_selector",
@@ -36,12 +100,12 @@ pub struct ResponseSyncCheck {
 
 impl ResponseSyncCheck {
     fn is_success(&self) -> bool {
-        self.ret_code == "0"
+        self.ret_code == RetCode::Success
     }
 
     pub fn error(self) -> Result<Self, Error> {
         if !self.is_success() {
-            return Err(Error::SyncCheck(self.ret_code));
+            return Err(Error::SyncCheckRetCode(self.ret_code));
         }
 
         Ok(self)
@@ -56,22 +120,28 @@ impl ResponseSyncCheck {
     // }
 }
 
+fn de_ret_code<'de, D>(deserializer: D) -> Result<RetCode, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Ok(RetCode::from(s.as_str()))
+}
+
+fn ser_ret_code<S>(ret_code: &RetCode, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&ret_code.to_string())
+}
+
 fn de_selector<'de, D>(deserializer: D) -> Result<Selector, D::Error>
 where
     D: Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    match s.as_str() {
-        "0" => Ok(Selector::Normal),
-        "2" => Ok(Selector::NewMessage),
-        "4" => Ok(Selector::ModContact),
-        "6" => Ok(Selector::AddOrDelContact),
-        "7" => Ok(Selector::ModChatroom),
-        _ => Err(serde::de::Error::unknown_variant(
-            &s,
-            &["0", "1", "2", "3", "4"],
-        )),
-    }
+    // 未知的值会解析为Selector::Unknown, 不会失败
+    s.parse().map_err(serde::de::Error::custom)
 }
 
 pub fn ser_selector<S>(selector: &Selector, serializer: S) -> Result<S::Ok, S::Error>
@@ -97,4 +167,27 @@ mod tests {
         let resp: ResponseSyncCheck = serde_json::from_str(&corrected_json).unwrap();
         dbg!(resp);
     }
+
+    #[test]
+    fn test_parse_ret_code_and_selector() {
+        let resp: ResponseSyncCheck =
+            serde_json::from_str(r#"{"retcode":"1101","selector":"0"}"#).unwrap();
+        assert_eq!(resp.ret_code, RetCode::LogoutElsewhere);
+        assert!(resp.ret_code.is_logout());
+        assert!(resp.error().is_err());
+
+        let resp: ResponseSyncCheck =
+            serde_json::from_str(r#"{"retcode":"1205","selector":"3"}"#).unwrap();
+        assert_eq!(resp.ret_code, RetCode::Unknown("1205".to_string()));
+        assert!(!resp.ret_code.is_logout());
+        assert_eq!(resp.selector, Selector::MobileSync);
+
+        let resp: ResponseSyncCheck =
+            serde_json::from_str(r#"{"retcode":"0","selector":"9"}"#).unwrap();
+        assert_eq!(resp.selector, Selector::Unknown("9".to_string()));
+        assert_eq!(
+            serde_json::to_string(&resp).unwrap(),
+            r#"{"retcode":"0","selector":"9"}"#
+        );
+    }
 }
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{contact::Contact, message::Message};
+
 use super::{BaseResponse, SyncKey};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,19 +11,19 @@ pub struct ResponseSyncMessage {
     #[serde(rename = "AddMsgCount")]
     pub add_msg_count: usize,
     #[serde(rename = "AddMsgList")]
-    pub add_msg_list: Vec<serde_json::Value>,
+    pub add_msg_list: Vec<Message>,
     #[serde(rename = "ModContactCount")]
     pub mod_contact_count: usize,
     #[serde(rename = "ModContactList")]
-    pub mod_contact_list: Vec<serde_json::Value>,
+    pub mod_contact_list: Vec<Contact>,
     #[serde(rename = "DelContactCount")]
     pub del_contact_count: usize,
     #[serde(rename = "DelContactList")]
-    pub del_contact_list: Vec<serde_json::Value>,
+    pub del_contact_list: Vec<Contact>,
     #[serde(rename = "ModChatRoomMemberCount")]
     pub mod_chat_room_member_count: usize,
     #[serde(rename = "ModChatRoomMemberList")]
-    pub mod_chat_room_member_list: Vec<serde_json::Value>,
+    pub mod_chat_room_member_list: Vec<Contact>,
     #[serde(rename = "Profile")]
     pub profile: Profile,
     #[serde(rename = "ContinueFlag")]
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use super::BaseResponse;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseCheckUpload {
+    #[serde(rename = "BaseResponse")]
+    pub base_response: BaseResponse,
+    /// 文件已经上传过时返回MediaId, 否则为空
+    #[serde(rename = "MediaId", default)]
+    pub media_id: String,
+    #[serde(rename = "AESKey", default)]
+    pub aes_key: String,
+    #[serde(rename = "Signature", default)]
+    pub signature: String,
+    #[serde(rename = "EntryFileName", default)]
+    pub entry_file_name: String,
+    #[serde(rename = "EncryFileName", default)]
+    pub encry_file_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseUploadMedia {
+    #[serde(rename = "BaseResponse")]
+    pub base_response: BaseResponse,
+    #[serde(rename = "MediaId", default)]
+    pub media_id: String,
+    #[serde(rename = "StartPos", default)]
+    pub start_pos: i64,
+    #[serde(rename = "CDNThumbImgHeight", default)]
+    pub cdn_thumb_img_height: i32,
+    #[serde(rename = "CDNThumbImgWidth", default)]
+    pub cdn_thumb_img_width: i32,
+    #[serde(rename = "EncryFileName", default)]
+    pub encry_file_name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_upload_media() {
+        let json = r#"{"BaseResponse":{"Ret":0,"ErrMsg":""},"MediaId":"@crypt_abc","StartPos":1024,"CDNThumbImgHeight":100,"CDNThumbImgWidth":75,"EncryFileName":"a%2Epng"}"#;
+        let resp: ResponseUploadMedia = serde_json::from_str(json).unwrap();
+        assert!(resp.base_response.is_ok());
+        assert_eq!(resp.media_id, "@crypt_abc");
+        assert_eq!(resp.start_pos, 1024);
+    }
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+use super::BaseResponse;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseVerifyUser {
+    #[serde(rename = "BaseResponse")]
+    pub base_response: BaseResponse,
+}
@@ -2,7 +2,9 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
-use super::{user::User, BaseResponse};
+use crate::contact::Contact;
+
+use super::BaseResponse;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseWebInit {
@@ -11,11 +13,11 @@ pub struct ResponseWebInit {
     #[serde(rename = "Count")]
     pub count: i32,
     #[serde(rename = "ContactList")]
-    pub contact_list: Vec<serde_json::Value>,
+    pub contact_list: Vec<Contact>,
     #[serde(rename = "SyncKey")]
     pub sync_key: SyncKey,
     #[serde(rename = "User")]
-    pub user: User,
+    pub user: Contact,
     #[serde(rename = "ChatSet")]
     pub chat_set: String,
     #[serde(rename = "SKey")]
@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use log::debug;
+
+use crate::Error;
+
+use super::{HotReloadStorage, HotReloadStorageItem, JSONFileHostReloadStorage};
+
+/// 每个账号保存在单独目录中的文件名
+const ACCOUNT_STORAGE_FILE: &str = "storage.json";
+
+/// 按账号分目录保存的热登录存储, 同一个进程运行多个Bot时互不覆盖
+///
+/// 登录信息保存在`root/account/storage.json`, `account`为调用方自定义的账号标识
+pub struct AccountDirStorage {
+    dir: PathBuf,
+    inner: JSONFileHostReloadStorage,
+}
+
+impl AccountDirStorage {
+    pub fn new(root: impl AsRef<Path>, account: &str) -> Self {
+        let dir = root.as_ref().join(account);
+        let inner = JSONFileHostReloadStorage::new(dir.join(ACCOUNT_STORAGE_FILE));
+        Self { dir, inner }
+    }
+
+    /// 账号的存储目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 列出`root`下已经保存过登录信息的账号
+    pub async fn accounts(root: impl AsRef<Path>) -> Result<Vec<String>, Error> {
+        let root = root.as_ref();
+        let mut entries = match tokio::fs::read_dir(root).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(Error::OpenFile(format!(
+                    "读取目录{}失败: {e}",
+                    root.display()
+                )))
+            }
+        };
+
+        let mut accounts = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| Error::OpenFile(format!("读取目录{}失败: {e}", root.display())))?
+        {
+            if entry.path().join(ACCOUNT_STORAGE_FILE).is_file() {
+                accounts.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+        accounts.sort();
+        Ok(accounts)
+    }
+}
+
+#[async_trait]
+impl HotReloadStorage for AccountDirStorage {
+    async fn dump(&mut self, item: &HotReloadStorageItem) -> Result<(), Error> {
+        debug!("AccountDirStorage::dump {}", self.dir.display());
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| Error::OpenFile(format!("创建目录{}失败: {e}", self.dir.display())))?;
+        self.inner.dump(item).await
+    }
+
+    async fn fetch(&mut self) -> Result<HotReloadStorageItem, Error> {
+        self.inner.fetch().await
+    }
+
+    async fn clear(&mut self) -> Result<(), Error> {
+        debug!("AccountDirStorage::clear {}", self.dir.display());
+        self.inner.clear().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_path;
+
+    #[tokio::test]
+    async fn test_account_dir_storage() {
+        let root = test_path("accounts");
+        let _ = tokio::fs::remove_dir_all(&root).await;
+
+        let item = HotReloadStorageItem::test();
+        let mut alice = AccountDirStorage::new(&root, "alice");
+        let mut bob = AccountDirStorage::new(&root, "bob");
+        alice.dump(&item).await.unwrap();
+        bob.dump(&item).await.unwrap();
+        assert_eq!(
+            AccountDirStorage::accounts(&root).await.unwrap(),
+            vec!["alice", "bob"]
+        );
+        assert_eq!(alice.fetch().await.unwrap().uuid, item.uuid);
+
+        bob.clear().await.unwrap();
+        assert_eq!(
+            AccountDirStorage::accounts(&root).await.unwrap(),
+            vec!["alice"]
+        );
+
+        tokio::fs::remove_dir_all(&root).await.unwrap();
+    }
+}
@@ -1,97 +1,281 @@
-use log::debug;
-use serde::Serialize;
-use tokio::{
-    fs::{File, OpenOptions},
-    io::{AsyncReadExt, AsyncWriteExt},
-};
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use log::{debug, warn};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
 
 use crate::{
-    storage::{HotReloadStorageItem, StorageItemFetcher},
+    storage::{HotReloadStorage, HotReloadStorageItem},
     Error,
 };
 
+/// 保存到JSON文件的热登录存储, 默认文件为当前目录下的storage.json
+///
+/// 先写入临时文件并同步到磁盘, 再通过rename替换原文件, 写入过程中崩溃不会损坏已保存的数据。
+/// 替换前的文件复制为`.bak`, 原文件损坏时依次从临时文件和备份恢复
 pub struct JSONFileHostReloadStorage {
-    filename: String,
-    file: Option<File>,
+    filename: PathBuf,
 }
 
-unsafe impl Sync for JSONFileHostReloadStorage {}
-unsafe impl Send for JSONFileHostReloadStorage {}
-
 impl Default for JSONFileHostReloadStorage {
     fn default() -> Self {
-        Self {
-            filename: "storage.json".to_string(),
-            file: None,
-        }
+        Self::new("storage.json")
     }
 }
 
-impl StorageItemFetcher for JSONFileHostReloadStorage {
-    async fn dump<T: Serialize>(&mut self, data: T) -> Result<(), Error> {
-        debug!("JSONFileHostReloadStorage::dump");
-        if self.file.is_none() {
-            let file = OpenOptions::new()
-                .create(true)
-                .truncate(false)
-                .write(true)
-                .read(true)
-                .open(&self.filename)
-                .await
-                .map_err(|e| Error::OpenFile(format!("打开文件{}失败: {e}", self.filename)))?;
-
-            self.file = Some(file);
+impl JSONFileHostReloadStorage {
+    pub fn new(filename: impl Into<PathBuf>) -> Self {
+        Self {
+            filename: filename.into(),
         }
+    }
+
+    /// 上一次保存的数据
+    fn backup_path(&self) -> PathBuf {
+        with_suffix(&self.filename, "bak")
+    }
 
-        let buf = serde_json::to_vec(&data)?;
+    /// 写入中的数据, rename之后才会生效
+    fn temp_path(&self) -> PathBuf {
+        with_suffix(&self.filename, "tmp")
+    }
+}
 
-        let file = self.file.as_mut().unwrap();
+#[async_trait]
+impl HotReloadStorage for JSONFileHostReloadStorage {
+    async fn dump(&mut self, item: &HotReloadStorageItem) -> Result<(), Error> {
+        debug!("JSONFileHostReloadStorage::dump");
+        let buf = serde_json::to_vec(item)?;
 
+        let temp_path = self.temp_path();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(&temp_path)
+            .await
+            .map_err(|e| Error::OpenFile(format!("打开文件{}失败: {e}", temp_path.display())))?;
         file.write_all(&buf)
             .await
             .map_err(|e| Error::OpenFile(format!("写入文件失败: {e}")))?;
-
-        file.sync_data()
+        file.sync_all()
             .await
             .map_err(|e| Error::OpenFile(format!("同步文件数据失败: {e}")))?;
+        drop(file);
+
+        // 原文件有效时才作为备份, 避免用损坏的数据覆盖上一份备份。
+        // 复制而不是移动原文件, 保证替换完成之前原文件一直存在
+        if read_item(&self.filename).await.is_ok() {
+            let backup_path = self.backup_path();
+            tokio::fs::copy(&self.filename, &backup_path)
+                .await
+                .map_err(|e| {
+                    Error::OpenFile(format!("备份文件{}失败: {e}", self.filename.display()))
+                })?;
+            sync_file(&backup_path).await?;
+        }
+        // rename会原子地替换原文件
+        tokio::fs::rename(&temp_path, &self.filename)
+            .await
+            .map_err(|e| {
+                Error::OpenFile(format!("替换文件{}失败: {e}", self.filename.display()))
+            })?;
+        sync_dir(&self.filename).await;
 
         Ok(())
     }
 
     async fn fetch(&mut self) -> Result<HotReloadStorageItem, Error> {
-        if self.file.is_none() {
-            let file = OpenOptions::new()
-                .create(true)
-                .truncate(false)
-                .write(true)
-                .read(true)
-                .open(&self.filename)
-                .await
-                .map_err(|e| Error::OpenFile(format!("打开文件{}失败: {e}", self.filename)))?;
-            self.file = Some(file);
+        let err = match read_item(&self.filename).await {
+            Ok(item) => return Ok(item),
+            Err(e) => e,
+        };
+
+        // 已经同步到磁盘但还没有替换原文件的临时文件比备份更新
+        for path in [self.temp_path(), self.backup_path()] {
+            if let Ok(item) = read_item(&path).await {
+                warn!(
+                    "读取{}失败: {err}, 使用{}",
+                    self.filename.display(),
+                    path.display()
+                );
+                return Ok(item);
+            }
         }
+        Err(err)
+    }
 
-        let mut buf = Vec::new();
-        self.file
-            .as_mut()
-            .unwrap()
-            .read_to_end(&mut buf)
-            .await
-            .map_err(|e| Error::OpenFile(format!("读取文件失败: {e}")))?;
+    async fn clear(&mut self) -> Result<(), Error> {
+        debug!("JSONFileHostReloadStorage::clear");
+        for path in [self.temp_path(), self.backup_path(), self.filename.clone()] {
+            match tokio::fs::remove_file(&path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(Error::OpenFile(format!(
+                        "删除文件{}失败: {e}",
+                        path.display()
+                    )))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn read_item(path: &Path) -> Result<HotReloadStorageItem, Error> {
+    let buf = tokio::fs::read(path)
+        .await
+        .map_err(|e| Error::FetchStorage(format!("读取文件{}失败: {e}", path.display())))?;
+    serde_json::from_slice(&buf)
+        .map_err(|e| Error::FetchStorage(format!("解析文件{}失败: {e}", path.display())))
+}
 
-        Ok(serde_json::from_slice(&buf)?)
+/// 在文件名后追加后缀, 例如storage.json.bak
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+async fn sync_file(path: &Path) -> Result<(), Error> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| Error::OpenFile(format!("打开文件{}失败: {e}", path.display())))?;
+    file.sync_all()
+        .await
+        .map_err(|e| Error::OpenFile(format!("同步文件{}失败: {e}", path.display())))
+}
+
+/// 同步所在目录, 保证rename在断电后仍然有效, 不支持的平台忽略
+async fn sync_dir(path: &Path) {
+    #[cfg(unix)]
+    {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        if let Ok(file) = tokio::fs::File::open(dir).await {
+            if let Err(e) = file.sync_all().await {
+                warn!("同步目录{}失败: {e}", dir.display());
+            }
+        }
     }
+    #[cfg(not(unix))]
+    let _ = path;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::test_path;
 
     #[tokio::test]
     async fn test_dump() {
         let json_str = r#"{"cookies":{"https://wx2.qq.com/cgi-bin/mmwebwx-bin/webwxstatusnotify":"","https://wx2.qq.com/cgi-bin/mmwebwx-bin/webwxinit":"","https://login.wx.qq.com/cgi-bin/mmwebwx-bin/login":"","https://wx2.qq.com/cgi-bin/mmwebwx-bin/webwxnewloginpage":"{\"raw_cookie\":\"Expires=Fri, 05-Jan-2035 06:18:22 GMT\",\"path\":[\"/cgi-bin/mmwebwx-bin\",false],\"domain\":{\"HostOnly\":\"wx2.qq.com\"},\"expires\":\"SessionEnd\"}\n{\"raw_cookie\":\"webwx_auth_ticket=CIsBEMLbgagKGoABoPfuX4SSdHsh4DC5Rdw37msyozVfjAMBvYT/pTuSlEDUuBxco1Z7ayZA3gdmCb0R40rUJAQgQk6Ay0lmHfxTFT3kER6AZBvrOkzisSTWnMw8MpiAtacpVSOQeEMN+Z4j5TrclsCFqX5e68jWT2zN2f9G8IVWFAb6mc+5ssCxJzc=\",\"path\":[\"/cgi-bin/mmwebwx-bin\",false],\"domain\":{\"HostOnly\":\"wx2.qq.com\"},\"expires\":\"SessionEnd\"}\n{\"raw_cookie\":\"wxsid=yCYetV96I2j/88wO\",\"path\":[\"/cgi-bin/mmwebwx-bin\",false],\"domain\":{\"HostOnly\":\"wx2.qq.com\"},\"expires\":\"SessionEnd\"}\n{\"raw_cookie\":\"Domain=wx2.qq.com\",\"path\":[\"/cgi-bin/mmwebwx-bin\",false],\"domain\":{\"HostOnly\":\"wx2.qq.com\"},\"expires\":\"SessionEnd\"}\n{\"raw_cookie\":\"webwx_data_ticket=gSe8IBRv4xqULn2LNy4M9x4L\",\"path\":[\"/cgi-bin/mmwebwx-bin\",false],\"domain\":{\"HostOnly\":\"wx2.qq.com\"},\"expires\":\"SessionEnd\"}\n{\"raw_cookie\":\"mm_lang=zh_CN\",\"path\":[\"/cgi-bin/mmwebwx-bin\",false],\"domain\":{\"HostOnly\":\"wx2.qq.com\"},\"expires\":\"SessionEnd\"}\n{\"raw_cookie\":\"webwxuvid=26a1cb79c1c0c1bfbc1912994c124abc6465d9e52f589b54cbe7215b848629a5bc70398fda991fa3281ca5523741211b\",\"path\":[\"/cgi-bin/mmwebwx-bin\",false],\"domain\":{\"HostOnly\":\"wx2.qq.com\"},\"expires\":\"SessionEnd\"}\n{\"raw_cookie\":\"wxloadtime=1736230702\",\"path\":[\"/cgi-bin/mmwebwx-bin\",false],\"domain\":{\"HostOnly\":\"wx2.qq.com\"},\"expires\":\"SessionEnd\"}\n{\"raw_cookie\":\"wxuin=2850172843\",\"path\":[\"/cgi-bin/mmwebwx-bin\",false],\"domain\":{\"HostOnly\":\"wx2.qq.com\"},\"expires\":\"SessionEnd\"}\n{\"raw_cookie\":\"Path=/\",\"path\":[\"/cgi-bin/mmwebwx-bin\",false],\"domain\":{\"HostOnly\":\"wx2.qq.com\"},\"expires\":\"SessionEnd\"}\n","https://login.wx.qq.com/jslogin":""},"base_request":{"Uin":2850172843,"Sid":"yCYetV96I2j/88wO","Skey":"@crypt_d7ac068f_100b9bea039ffda56dda4d47fcfd6a8d","DeviceID":"e354261774648813"},"login_info":{"ret":0,"wxuin":2850172843,"isgrayscale":1,"message":"","skey":"@crypt_d7ac068f_100b9bea039ffda56dda4d47fcfd6a8d","wxsid":"yCYetV96I2j/88wO","pass_ticket":"fDEumsZLaSwaFlPnBVY%2FSbIaTWT8SsWAwchbYGoTX9mdNQcvLyxGBkQzFxc3UGMsEGC3PbDtHEhyxm9If5rbqA%3D%3D"},"wechat_domain":"wx2.qq.com","uuid":"ob1vmlKrwA=="}"#;
         let items: HotReloadStorageItem = serde_json::from_str(json_str).unwrap();
-        let mut storage = JSONFileHostReloadStorage::default();
-        storage.dump(items).await.unwrap();
+        let mut storage = JSONFileHostReloadStorage::new(test_path("dump.json"));
+        storage.dump(&items).await.unwrap();
+        assert_eq!(storage.fetch().await.unwrap().uuid, items.uuid);
+        storage.clear().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clear() {
+        let filename = test_path("clear.json");
+        let mut storage = JSONFileHostReloadStorage::new(&filename);
+        storage.dump(&HotReloadStorageItem::test()).await.unwrap();
+        assert!(filename.exists());
+        storage.clear().await.unwrap();
+        assert!(!filename.exists());
+        // 文件不存在时不报错
+        storage.clear().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dump_replace() {
+        let filename = test_path("dump_replace.json");
+        let mut storage = JSONFileHostReloadStorage::new(&filename);
+        storage.clear().await.unwrap();
+
+        let mut item = HotReloadStorageItem::test();
+        item.uuid = Some("a".repeat(64));
+        storage.dump(&item).await.unwrap();
+        // 第二次写入的内容更短, 不能残留上一次的数据
+        storage.dump(&HotReloadStorageItem::test()).await.unwrap();
+        assert_eq!(
+            storage.fetch().await.unwrap().uuid,
+            HotReloadStorageItem::test().uuid
+        );
+        assert!(!storage.temp_path().exists());
+
+        let backup = tokio::fs::read(storage.backup_path()).await.unwrap();
+        let backup: HotReloadStorageItem = serde_json::from_slice(&backup).unwrap();
+        assert_eq!(backup.uuid, item.uuid);
+
+        storage.clear().await.unwrap();
+        assert!(!storage.backup_path().exists());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_recover() {
+        let filename = test_path("fetch_recover.json");
+        let mut storage = JSONFileHostReloadStorage::new(&filename);
+        storage.clear().await.unwrap();
+        assert!(storage.fetch().await.is_err());
+
+        storage.dump(&HotReloadStorageItem::test()).await.unwrap();
+        storage.dump(&HotReloadStorageItem::test()).await.unwrap();
+        // 模拟写入到一半时崩溃
+        tokio::fs::write(&filename, b"{\"cookies\":{")
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.fetch().await.unwrap().uuid,
+            HotReloadStorageItem::test().uuid
+        );
+
+        // 损坏的文件不会覆盖备份
+        let mut item = HotReloadStorageItem::test();
+        item.uuid = Some("new".to_string());
+        storage.dump(&item).await.unwrap();
+        assert_eq!(storage.fetch().await.unwrap().uuid, item.uuid);
+        let backup = tokio::fs::read(storage.backup_path()).await.unwrap();
+        let backup: HotReloadStorageItem = serde_json::from_slice(&backup).unwrap();
+        assert_eq!(backup.uuid, HotReloadStorageItem::test().uuid);
+
+        storage.clear().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_temp() {
+        let filename = test_path("fetch_temp.json");
+        let mut storage = JSONFileHostReloadStorage::new(&filename);
+        storage.clear().await.unwrap();
+
+        storage.dump(&HotReloadStorageItem::test()).await.unwrap();
+        storage.dump(&HotReloadStorageItem::test()).await.unwrap();
+        // 替换过程中原文件始终存在
+        assert!(filename.exists());
+
+        // 没有原文件, 只剩下备份和已经写完的临时文件
+        let mut item = HotReloadStorageItem::test();
+        item.uuid = Some("new".to_string());
+        tokio::fs::write(storage.temp_path(), serde_json::to_vec(&item).unwrap())
+            .await
+            .unwrap();
+        tokio::fs::remove_file(&filename).await.unwrap();
+        assert_eq!(storage.fetch().await.unwrap().uuid, item.uuid);
+
+        // 临时文件不完整时使用备份
+        tokio::fs::write(storage.temp_path(), b"{\"cookies\":{")
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.fetch().await.unwrap().uuid,
+            HotReloadStorageItem::test().uuid
+        );
+
+        storage.clear().await.unwrap();
     }
 }
@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use log::debug;
+
+use crate::Error;
+
+use super::{HotReloadStorage, HotReloadStorageItem};
+
+/// 保存在内存中的热登录存储, 进程退出后失效, 适用于测试或者由调用方自行持久化
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    /// 序列化后的登录信息, 和文件存储使用相同的格式
+    data: Option<Vec<u8>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 序列化后的JSON数据
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        self.data.as_deref()
+    }
+}
+
+#[async_trait]
+impl HotReloadStorage for MemoryStorage {
+    async fn dump(&mut self, item: &HotReloadStorageItem) -> Result<(), Error> {
+        debug!("MemoryStorage::dump");
+        self.data = Some(serde_json::to_vec(item)?);
+        Ok(())
+    }
+
+    async fn fetch(&mut self) -> Result<HotReloadStorageItem, Error> {
+        let data = self
+            .data
+            .as_ref()
+            .ok_or(Error::FetchStorage("没有保存的登录信息".to_owned()))?;
+        Ok(serde_json::from_slice(data)?)
+    }
+
+    async fn clear(&mut self) -> Result<(), Error> {
+        debug!("MemoryStorage::clear");
+        self.data = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_storage() {
+        let mut storage: Box<dyn HotReloadStorage> = Box::new(MemoryStorage::new());
+        assert!(storage.fetch().await.is_err());
+
+        let item = HotReloadStorageItem::test();
+        storage.dump(&item).await.unwrap();
+        let fetched = storage.fetch().await.unwrap();
+        assert_eq!(fetched.uuid, item.uuid);
+        assert_eq!(fetched.wechat_domain.unwrap().to_string(), "wx2.qq.com");
+
+        storage.clear().await.unwrap();
+        assert!(storage.fetch().await.is_err());
+    }
+}
@@ -1,21 +1,30 @@
 use std::collections::HashMap;
 
+use async_trait::async_trait;
 use reqwest_cookie_store::CookieStore;
 use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{
-    resp::{LoginInfo, ResponseWebInit},
+    resp::{LoginInfo, ResponseWebInit, SyncKey},
     Error,
 };
 
+pub use account::AccountDirStorage;
 pub use json::tokio::JSONFileHostReloadStorage;
+pub use memory::MemoryStorage;
+
+mod account;
 mod json;
+mod memory;
 
+/// 登录后的运行时状态, 不会持久化
 #[derive(Debug, Default, Serialize, Deserialize)]
-pub struct Storage {
+pub(crate) struct Storage {
     pub login_info: Option<LoginInfo>,
     pub request: Option<BaseRequest>,
     pub web_init_reponse: Option<ResponseWebInit>,
+    /// 心跳使用的SyncCheckKey, 首次同步之前使用SyncKey
+    pub sync_check_key: Option<SyncKey>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +39,7 @@ pub struct BaseRequest {
     pub device_id: String,
 }
 
+/// 热登录需要保存的数据
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HotReloadStorageItem {
     #[serde(serialize_with = "ser_cookies", deserialize_with = "de_cookies")]
@@ -58,10 +68,7 @@ where
     Ok(cookies)
 }
 
-pub fn ser_cookies<S>(
-    cookies: &HashMap<String, CookieStore>,
-    serializer: S,
-) -> Result<S::Ok, S::Error>
+fn ser_cookies<S>(cookies: &HashMap<String, CookieStore>, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
@@ -76,9 +83,15 @@ where
     map.end()
 }
 
-pub(crate) trait StorageItemFetcher {
-    async fn dump<T: Serialize>(&mut self, data: T) -> Result<(), Error>;
+/// 热登录数据的存储, 可以通过[`BotBuilder::hot_reload_storage`](crate::bot::BotBuilder::hot_reload_storage)使用自定义实现
+#[async_trait]
+pub trait HotReloadStorage: Send + Sync {
+    /// 登录成功后保存登录信息
+    async fn dump(&mut self, item: &HotReloadStorageItem) -> Result<(), Error>;
+    /// 热登录时读取保存的登录信息
     async fn fetch(&mut self) -> Result<HotReloadStorageItem, Error>;
+    /// 清除保存的登录信息, 退出登录后调用
+    async fn clear(&mut self) -> Result<(), Error>;
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -112,3 +125,23 @@ impl WechatDomain {
         format!("https://webpush.{}", self.0)
     }
 }
+
+#[cfg(test)]
+impl HotReloadStorageItem {
+    /// 测试使用的登录信息
+    pub(crate) fn test() -> Self {
+        Self {
+            cookies: Default::default(),
+            base_request: None,
+            login_info: None,
+            wechat_domain: Some(WechatDomain::new("wx2.qq.com".to_string())),
+            uuid: Some("ob1vmlKrwA==".to_string()),
+        }
+    }
+}
+
+/// 测试使用的临时路径, 包含进程id和测试名称, 并行运行的测试互不影响
+#[cfg(test)]
+pub(crate) fn test_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("openwechat_test_{}_{name}", std::process::id()))
+}